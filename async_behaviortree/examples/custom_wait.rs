@@ -141,7 +141,7 @@ fn main() -> Result<(), String> {
 
     let delta_rx = executor.tick_channel();
 
-    let (future, _controller) = AsyncBehaviorTree::new(behavior, false, delta_rx, bot);
+    let (future, _controller) = AsyncBehaviorTree::new(behavior, false, delta_rx, bot, ());
 
     executor
         .spawn_local("AsyncBehaviorTree::future", future)
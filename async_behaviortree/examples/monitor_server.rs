@@ -0,0 +1,74 @@
+//! Serves a looping tree to remote viewers, run with
+//! `cargo run --features monitor --example monitor_server [-- --websocket]`
+//!
+//! Watch it with the `monitor_client` example of `behaviortree`, the protocol is shared.
+
+use std::io;
+use std::time::Duration;
+
+use async_behaviortree::{
+    AsyncActionName, AsyncActionRunner, AsyncBehaviorTree, Behavior, MonitorServer, StateObserver,
+};
+use ticked_async_executor::TickedAsyncExecutor;
+
+const ADDR: &str = "127.0.0.1:7878";
+const DT: f64 = 0.1;
+
+struct Toggle;
+
+impl AsyncActionName for Toggle {
+    fn name(&self) -> &str {
+        "Toggle"
+    }
+}
+
+/// Succeeds every other run
+#[derive(Default)]
+struct Lamp {
+    on: bool,
+}
+
+#[async_trait::async_trait(?Send)]
+impl AsyncActionRunner<Toggle> for Lamp {
+    async fn run(&mut self, _delta: tokio::sync::watch::Receiver<f64>, _action: &Toggle) -> bool {
+        self.on = !self.on;
+        self.on
+    }
+
+    fn reset(&mut self, _action: &Toggle) {}
+}
+
+fn main() -> io::Result<()> {
+    let behavior = Behavior::Select(vec![
+        Behavior::Named(
+            "Blink".into(),
+            Box::new(Behavior::Sequence(vec![
+                Behavior::Action(Toggle),
+                Behavior::Named("On".into(), Box::new(Behavior::Wait(1.0))),
+            ])),
+        ),
+        Behavior::Named("Off".into(), Box::new(Behavior::Wait(0.5))),
+    ]);
+    let mut executor = TickedAsyncExecutor::default();
+    let (future, controller) = AsyncBehaviorTree::new(
+        behavior,
+        true,
+        executor.tick_channel(),
+        Lamp::default(),
+        StateObserver::default(),
+    );
+    executor.spawn_local("Blink", future).detach();
+
+    let mut server = if std::env::args().any(|arg| arg == "--websocket") {
+        MonitorServer::bind_websocket(ADDR)?
+    } else {
+        MonitorServer::bind(ADDR)?
+    };
+    println!("Serving on {}", server.local_addr()?);
+
+    loop {
+        executor.tick(DT, None);
+        server.publish(&controller.state());
+        std::thread::sleep(Duration::from_secs_f64(DT));
+    }
+}
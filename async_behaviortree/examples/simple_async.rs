@@ -1,6 +1,8 @@
 use std::{collections::HashMap, rc::Rc, sync::RwLock};
 
-use async_behaviortree::{AsyncActionName, AsyncActionRunner, AsyncBehaviorTree, Behavior};
+use async_behaviortree::{
+    AsyncActionName, AsyncActionRunner, AsyncBehaviorTree, Behavior, StateObserver,
+};
 use ticked_async_executor::TickedAsyncExecutor;
 
 #[derive(Debug, Clone, Copy, serde::Serialize)]
@@ -122,7 +124,8 @@ fn main() -> Result<(), String> {
     let mut executor = TickedAsyncExecutor::default();
     let delta_rx = executor.tick_channel();
 
-    let (future, controller) = AsyncBehaviorTree::new(behavior, false, delta_rx, bot);
+    let (future, controller) =
+        AsyncBehaviorTree::new(behavior, false, delta_rx, bot, StateObserver::default());
 
     executor
         .spawn_local("AsyncBehaviorTree::future", future)
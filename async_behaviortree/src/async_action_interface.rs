@@ -1,5 +1,6 @@
 pub trait AsyncActionName {
-    fn name(&self) -> &'static str;
+    /// Identify your action, may borrow an owned name from the action
+    fn name(&self) -> &str;
 }
 
 #[async_trait::async_trait(?Send)]
@@ -1,28 +1,86 @@
+use std::cell::{Ref, RefCell};
+use std::rc::Rc;
+
 use tokio_util::sync::CancellationToken;
 
 use crate::AsyncActionName;
 use crate::AsyncActionRunner;
 use crate::Behavior;
+use crate::BuildError;
+#[cfg(feature = "metrics")]
+use crate::Metrics;
+use crate::NodeId;
 use crate::State;
-use crate::async_child::AsyncChild;
+use crate::StateChanges;
+use crate::StateObserver;
+use crate::Status;
+use crate::TreeObserver;
+use crate::async_child::{AsyncChild, MetricsRef, observer_ref};
 use crate::util::yield_now;
 
-pub struct AsyncBehaviorController {
-    state: State,
+pub struct AsyncBehaviorController<A, O = ()> {
+    observer: Rc<RefCell<O>>,
+    #[cfg(feature = "metrics")]
+    metrics: MetricsRef,
     cancellation: CancellationToken,
+    reload: tokio::sync::mpsc::UnboundedSender<Behavior<A>>,
 }
 
-impl AsyncBehaviorController {
+impl<A, O> AsyncBehaviorController<A, O> {
     pub fn cancel_token(&self) -> CancellationToken {
         self.cancellation.clone()
     }
 
+    /// Resets the running tree and replaces it with `behavior`, keeping the runner
+    ///
+    /// The tree is replaced the next time its future is polled, the new nodes are
+    /// reported to the observer with fresh ids before the current ones are removed.
+    /// Returns `behavior` back when the tree future has completed or has been dropped.
+    pub fn replace_behavior(&self, behavior: Behavior<A>) -> Result<(), Behavior<A>> {
+        self.reload.send(behavior).map_err(|error| error.0)
+    }
+
+    pub fn observer(&self) -> Ref<'_, O> {
+        self.observer.borrow()
+    }
+
+    /// Counters of every node keyed by path, collected around every poll of the node futures
+    ///
+    /// Replaced behaviors start over from zero.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> Metrics {
+        let slots = self.metrics.borrow();
+        slots
+            .iter()
+            .map(|(path, slot)| (path.clone(), slot.get()))
+            .collect()
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn reset_metrics(&self) {
+        for slot in self.metrics.borrow().values() {
+            slot.take();
+        }
+    }
+}
+
+impl<A> AsyncBehaviorController<A, StateObserver> {
     pub fn state(&self) -> State {
-        self.state.clone()
+        self.observer.borrow().state()
+    }
+
+    /// Status changes of every node of the current tree, see [`StateObserver::changes`]
+    pub fn changes(&self) -> StateChanges {
+        self.observer.borrow_mut().changes()
+    }
+
+    /// Last status of the node `id`, see [`StateObserver::node_status`]
+    pub fn node_status(&self, id: NodeId) -> Option<Status> {
+        self.observer.borrow().node_status(id)
     }
 }
 
-impl Drop for AsyncBehaviorController {
+impl<A, O> Drop for AsyncBehaviorController<A, O> {
     fn drop(&mut self) {
         self.cancellation.cancel();
     }
@@ -31,52 +89,105 @@ impl Drop for AsyncBehaviorController {
 pub struct AsyncBehaviorTree;
 
 impl AsyncBehaviorTree {
-    pub fn new<A, R>(
+    pub fn new<A, R, O>(
         behavior: Behavior<A>,
         should_loop: bool,
         delta: tokio::sync::watch::Receiver<f64>,
         mut runner: R,
+        observer: O,
     ) -> (
         impl std::future::Future<Output = ()>,
-        AsyncBehaviorController,
+        AsyncBehaviorController<A, O>,
     )
     where
         A: AsyncActionName + 'static,
         R: AsyncActionRunner<A> + 'static,
+        O: TreeObserver + 'static,
     {
         let cancellation = tokio_util::sync::CancellationToken::new();
         let cancellation_clone = cancellation.clone();
 
-        let (mut child, state) = AsyncChild::from_behavior_with_state(behavior);
+        let observer = Rc::new(RefCell::new(observer));
+        let node_observer = observer_ref(&observer);
+        #[cfg_attr(not(feature = "metrics"), allow(clippy::let_unit_value))]
+        let tree_metrics = MetricsRef::default();
+        let mut next_id = 0;
+        let mut child = AsyncChild::from_behavior_with_observer(
+            behavior,
+            &node_observer,
+            &tree_metrics,
+            &mut next_id,
+        );
+        #[cfg(feature = "metrics")]
+        let metrics = tree_metrics.clone();
+        let (reload, mut reload_rx) = tokio::sync::mpsc::unbounded_channel();
         let future = async move {
-            if should_loop {
-                cancellation_clone
-                    .run_until_cancelled_owned(async {
-                        loop {
-                            let _status = child.run(delta.clone(), &mut runner).await;
-                            yield_now().await;
+            cancellation_clone
+                .run_until_cancelled_owned(async {
+                    loop {
+                        let reloaded = tokio::select! {
+                            biased;
+                            Some(behavior) = reload_rx.recv() => Some(behavior),
+                            _status = child.run(delta.clone(), &mut runner) => None,
+                        };
+                        if let Some(behavior) = reloaded {
                             child.reset(&mut runner);
+                            #[cfg(feature = "metrics")]
+                            tree_metrics.borrow_mut().clear();
+                            child = AsyncChild::from_behavior_with_observer(
+                                behavior,
+                                &node_observer,
+                                &tree_metrics,
+                                &mut next_id,
+                            );
+                            continue;
                         }
-                    })
-                    .await;
-            } else {
-                cancellation_clone
-                    .run_until_cancelled_owned(async {
-                        let _status = child.run(delta, &mut runner).await;
+
                         yield_now().await;
-                    })
-                    .await;
-            }
+                        if !should_loop {
+                            break;
+                        }
+                        child.reset(&mut runner);
+                    }
+                })
+                .await;
             child.reset(&mut runner);
         };
         (
             future,
             AsyncBehaviorController {
-                state,
+                observer,
+                #[cfg(feature = "metrics")]
+                metrics,
                 cancellation,
+                reload,
             },
         )
     }
+
+    /// Same as [`AsyncBehaviorTree::new`], but rejects behaviors that fail [`Behavior::validate`]
+    #[allow(clippy::type_complexity)]
+    pub fn try_new<A, R, O>(
+        behavior: Behavior<A>,
+        should_loop: bool,
+        delta: tokio::sync::watch::Receiver<f64>,
+        runner: R,
+        observer: O,
+    ) -> Result<
+        (
+            impl std::future::Future<Output = ()>,
+            AsyncBehaviorController<A, O>,
+        ),
+        Vec<BuildError>,
+    >
+    where
+        A: AsyncActionName + 'static,
+        R: AsyncActionRunner<A> + 'static,
+        O: TreeObserver + 'static,
+    {
+        behavior.validate()?;
+        Ok(Self::new(behavior, should_loop, delta, runner, observer))
+    }
 }
 
 #[cfg(test)]
@@ -87,7 +198,10 @@ mod tests {
     use ticked_async_executor::TickedAsyncExecutor;
     use tokio_stream::StreamExt;
 
+    #[cfg(feature = "metrics")]
+    use crate::NodeMetrics;
     use crate::test_async_behavior_interface::{DELTA, TestAction, TestRunner};
+    use crate::test_observer::TestObserver;
 
     #[test]
     fn test_async_behaviortree() {
@@ -99,8 +213,13 @@ mod tests {
         let mut executor = TickedAsyncExecutor::default();
         let runner = TestRunner;
 
-        let (behaviortree_future, controller) =
-            AsyncBehaviorTree::new(behavior, false, executor.tick_channel(), runner);
+        let (behaviortree_future, controller) = AsyncBehaviorTree::new(
+            behavior,
+            false,
+            executor.tick_channel(),
+            runner,
+            StateObserver::default(),
+        );
 
         let state = controller.state();
         let cancel = controller.cancel_token();
@@ -131,7 +250,10 @@ mod tests {
                         }
                     };
 
-                    streams.insert(name, tokio_stream::wrappers::WatchStream::new(rx.clone()));
+                    streams.insert(
+                        name.id,
+                        tokio_stream::wrappers::WatchStream::new(rx.clone()),
+                    );
                 }
 
                 let fut = async move {
@@ -167,6 +289,319 @@ mod tests {
         assert_eq!(executor.num_tasks(), 0);
     }
 
+    #[test]
+    fn test_async_behaviortree_try_new() {
+        let mut executor = TickedAsyncExecutor::default();
+
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Wait(0.0),
+        ]);
+        let (behaviortree_future, _controller) =
+            AsyncBehaviorTree::try_new(behavior, false, executor.tick_channel(), TestRunner, ())
+                .unwrap();
+        executor
+            .spawn_local("AsyncBehaviorTreeFuture", behaviortree_future)
+            .detach();
+        executor.wait_till_completed(DELTA);
+        assert_eq!(executor.num_tasks(), 0);
+
+        let behavior = Behavior::Select(vec![Behavior::Sequence(vec![]), Behavior::Wait(f64::NAN)]);
+        let errors = AsyncBehaviorTree::try_new::<TestAction, _, _>(
+            behavior,
+            false,
+            executor.tick_channel(),
+            TestRunner,
+            (),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0],
+            BuildError::EmptyComposite {
+                path: "root/Select[0]/Sequence".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_async_behaviortree_replace_behavior() {
+        let behavior = Behavior::Action(TestAction::SuccessAfter { times: 5 });
+
+        let mut executor = TickedAsyncExecutor::default();
+        let (behaviortree_future, controller) = AsyncBehaviorTree::new(
+            behavior,
+            false,
+            executor.tick_channel(),
+            TestRunner,
+            TestObserver::default(),
+        );
+        executor
+            .spawn_local("AsyncBehaviorTreeFuture", behaviortree_future)
+            .detach();
+        executor.tick(DELTA, None);
+
+        let behavior = Behavior::Invert(Box::new(Behavior::Action(TestAction::Success)));
+        assert!(controller.replace_behavior(behavior).is_ok());
+        executor.wait_till_completed(DELTA);
+        assert_eq!(executor.num_tasks(), 0);
+
+        let observer = controller.observer();
+        assert_eq!(observer.names, ["SuccessAfter", "Success", "Invert"]);
+        use crate::Status::*;
+        assert_eq!(
+            observer.changes,
+            [
+                (0, None, Some(Running)),
+                (0, Some(Running), None),
+                (2, None, Some(Running)),
+                (1, None, Some(Running)),
+                (1, Some(Running), Some(Success)),
+                (2, Some(Running), Some(Failure)),
+                (2, Some(Failure), None),
+                (1, Some(Success), None),
+            ]
+        );
+        drop(observer);
+
+        // The tree future has completed
+        let behavior = Behavior::Action(TestAction::Success);
+        assert!(controller.replace_behavior(behavior).is_err());
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_async_behaviortree_metrics() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Invert(Behavior::Wait(DELTA * 2.5).into()),
+        ]);
+
+        let mut executor = TickedAsyncExecutor::default();
+        let (behaviortree_future, controller) =
+            AsyncBehaviorTree::new(behavior, false, executor.tick_channel(), TestRunner, ());
+        executor
+            .spawn_local("AsyncBehaviorTreeFuture", behaviortree_future)
+            .detach();
+        executor.wait_till_completed(DELTA);
+
+        let metrics = controller.metrics();
+        let counters = |path: &str| {
+            let metrics = metrics[path];
+            (metrics.successes, metrics.failures)
+        };
+        assert_eq!(metrics.len(), 4);
+        assert_eq!(counters("root/Sequence"), (0, 1));
+        assert_eq!(counters("root/Sequence[0]/Success"), (1, 0));
+        assert_eq!(counters("root/Sequence[1]/Invert"), (0, 1));
+        assert_eq!(counters("root/Sequence[1]/Invert/Wait"), (1, 0));
+
+        let root = metrics["root/Sequence"];
+        let wait = metrics["root/Sequence[1]/Invert/Wait"];
+        // Ticks are counted once per frame, not per poll of the future
+        assert_eq!(metrics["root/Sequence[0]/Success"].ticks, 1);
+        assert_eq!((root.ticks, wait.ticks), (4, 3));
+        // The frame the wait completes in is not spent running
+        assert_eq!(wait.running_time, DELTA * 2.0);
+        assert!((root.running_time - DELTA * 3.0).abs() < 1e-9);
+        assert!(root.tick_time >= wait.tick_time);
+
+        controller.reset_metrics();
+        assert!(
+            controller
+                .metrics()
+                .values()
+                .all(|metrics| *metrics == NodeMetrics::default())
+        );
+    }
+
+    #[test]
+    fn test_async_behaviortree_node_ids() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Action(TestAction::Success),
+        ]);
+
+        let mut executor = TickedAsyncExecutor::default();
+        let (behaviortree_future, controller) = AsyncBehaviorTree::new(
+            behavior,
+            false,
+            executor.tick_channel(),
+            TestRunner,
+            StateObserver::default(),
+        );
+        let state = controller.state();
+        let [first, second] = state.children() else {
+            unreachable!()
+        };
+        let (first, second) = (first.node().clone(), second.node().clone());
+        assert_ne!(first.id, second.id);
+        assert_eq!(&*first.path, "root/Sequence[0]/Success");
+        assert_eq!(&*second.path, "root/Sequence[1]/Success");
+        assert_eq!(state.find(second.id).unwrap().node(), &second);
+
+        executor
+            .spawn_local("AsyncBehaviorTreeFuture", behaviortree_future)
+            .detach();
+        executor.tick(DELTA, None);
+        assert_eq!(controller.node_status(first.id), Some(Status::Success));
+        assert_eq!(controller.node_status(second.id), None);
+    }
+
+    #[test]
+    fn test_async_behaviortree_state_changes() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Invert(Box::new(Behavior::Action(TestAction::Failure))),
+        ]);
+
+        let mut executor = TickedAsyncExecutor::default();
+        let (behaviortree_future, controller) = AsyncBehaviorTree::new(
+            behavior,
+            false,
+            executor.tick_channel(),
+            TestRunner,
+            StateObserver::default(),
+        );
+
+        let events = Rc::new(RefCell::new(vec![]));
+        let mut changes = controller.changes();
+        let events_clone = events.clone();
+        executor
+            .spawn_local("AsyncObserver", async move {
+                while let Some(event) = changes.next().await {
+                    events_clone.borrow_mut().push(event);
+                }
+            })
+            .detach();
+        executor
+            .spawn_local("AsyncBehaviorTreeFuture", behaviortree_future)
+            .detach();
+        executor.wait_till_completed(DELTA);
+
+        // Parents run before their children, every node is reset once the tree has completed
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                (NodeId(3), Some(Status::Running)),
+                (NodeId(0), Some(Status::Running)),
+                (NodeId(0), Some(Status::Success)),
+                (NodeId(2), Some(Status::Running)),
+                (NodeId(1), Some(Status::Running)),
+                (NodeId(1), Some(Status::Failure)),
+                (NodeId(2), Some(Status::Success)),
+                (NodeId(3), Some(Status::Success)),
+                (NodeId(3), None),
+                (NodeId(0), None),
+                (NodeId(2), None),
+                (NodeId(1), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_async_behaviortree_state_changes_replace_behavior() {
+        let behavior = Behavior::Action(TestAction::SuccessAfter { times: 5 });
+
+        let mut executor = TickedAsyncExecutor::default();
+        let (behaviortree_future, controller) = AsyncBehaviorTree::new(
+            behavior,
+            false,
+            executor.tick_channel(),
+            TestRunner,
+            StateObserver::default(),
+        );
+        let mut changes = controller.changes();
+        executor
+            .spawn_local("AsyncBehaviorTreeFuture", behaviortree_future)
+            .detach();
+        executor.tick(DELTA, None);
+
+        // The old node is reset before being removed, the new nodes are followed
+        let behavior = Behavior::Invert(Box::new(Behavior::Action(TestAction::Success)));
+        assert!(controller.replace_behavior(behavior).is_ok());
+        executor.wait_till_completed(DELTA);
+        assert_eq!(
+            changes.drain().collect::<Vec<_>>(),
+            vec![
+                (NodeId(0), Some(Status::Running)),
+                (NodeId(0), None),
+                (NodeId(2), Some(Status::Running)),
+                (NodeId(1), Some(Status::Running)),
+                (NodeId(1), Some(Status::Success)),
+                (NodeId(2), Some(Status::Failure)),
+                (NodeId(2), None),
+                (NodeId(1), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_async_behaviortree_state_snapshot() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Action(TestAction::Success),
+        ]);
+
+        let mut executor = TickedAsyncExecutor::default();
+        let (behaviortree_future, controller) = AsyncBehaviorTree::new(
+            behavior,
+            false,
+            executor.tick_channel(),
+            TestRunner,
+            StateObserver::default(),
+        );
+        let old = controller.state().snapshot();
+        assert_eq!(old.children[1].path, "root/Sequence[1]/Success");
+        assert_eq!(old.status, None);
+
+        executor
+            .spawn_local("AsyncBehaviorTreeFuture", behaviortree_future)
+            .detach();
+        executor.tick(DELTA, None);
+        let new = controller.state().snapshot();
+        assert_eq!(new.children[0].status, Some(Status::Success));
+
+        let mut remote = old.clone();
+        remote.apply(&old.diff(&new));
+        assert_eq!(remote, new);
+    }
+
+    #[test]
+    fn test_async_behaviortree_named() {
+        let behavior = Behavior::Named(
+            "Outer".into(),
+            Box::new(Behavior::Named(
+                "Inner".into(),
+                Box::new(Behavior::Sequence(vec![Behavior::Named(
+                    "Greet".into(),
+                    Box::new(Behavior::Action(TestAction::Success)),
+                )])),
+            )),
+        );
+
+        let executor = TickedAsyncExecutor::default();
+        let (_behaviortree_future, controller) = AsyncBehaviorTree::new(
+            behavior,
+            false,
+            executor.tick_channel(),
+            TestRunner,
+            StateObserver::default(),
+        );
+        let state = controller.state();
+        assert_eq!(&*state.node().name, "Sequence");
+        assert_eq!(state.node().label.as_deref(), Some("Outer"));
+        assert_eq!(&*state.node().path, "root/Sequence");
+
+        let [child] = state.children() else {
+            unreachable!()
+        };
+        assert_eq!(&*child.node().name, "Success");
+        assert_eq!(child.node().label.as_deref(), Some("Greet"));
+        assert_eq!(&*child.node().path, "root/Sequence[0]/Success");
+    }
+
     #[test]
     fn test_async_behaviortree_shutdown() {
         let behavior = Behavior::Sequence(vec![
@@ -179,8 +614,13 @@ mod tests {
         let mut executor = TickedAsyncExecutor::default();
         let runner = TestRunner;
 
-        let (behaviortree_future, controller) =
-            AsyncBehaviorTree::new(behavior, true, executor.tick_channel(), runner);
+        let (behaviortree_future, controller) = AsyncBehaviorTree::new(
+            behavior,
+            true,
+            executor.tick_channel(),
+            runner,
+            StateObserver::default(),
+        );
 
         executor
             .spawn_local("AsyncBehaviorTreeFuture", behaviortree_future)
@@ -1,23 +1,66 @@
+use std::any::TypeId;
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use crate::behavior_nodes::{
     AsyncAction, AsyncActionState, AsyncInvertState, AsyncSelectState, AsyncSequenceState,
     AsyncWaitState,
 };
-use crate::{AsyncActionName, AsyncActionRunner, State};
+#[cfg(feature = "metrics")]
+pub(crate) use crate::metrics::MetricsRef;
+#[cfg(feature = "metrics")]
+use crate::metrics::MetricsSlot;
+use crate::path::{ROOT, child_path};
+use crate::{AsyncActionName, AsyncActionRunner, NodeChildren, NodeId, TreeObserver};
 use crate::{Behavior, Status};
 
+/// Observer shared by the nodes, `None` when the tree has no observer
+/// Nodes keep no counters without the `metrics` feature
+#[cfg(not(feature = "metrics"))]
+pub(crate) type MetricsRef = ();
+
+pub type ObserverRef = Option<Rc<RefCell<dyn TreeObserver>>>;
+
+/// Skips the `()` observer, so that nodes of a tree without observer never call it
+pub fn observer_ref<O: TreeObserver + 'static>(observer: &Rc<RefCell<O>>) -> ObserverRef {
+    if TypeId::of::<O>() == TypeId::of::<()>() {
+        return None;
+    }
+    Some(observer.clone())
+}
+
 pub struct AsyncChild<R> {
     action_type: Box<dyn AsyncAction<R>>,
-    status: tokio::sync::watch::Sender<Option<Status>>,
+    id: NodeId,
+    /// i.e `root/Sequence[1]/Invert/Action`
+    #[cfg_attr(not(any(feature = "tracing", feature = "metrics")), allow(dead_code))]
+    path: String,
+    /// Display label of the node, see `Behavior::Named`
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    label: Option<String>,
+    status: Option<Status>,
+    observer: ObserverRef,
+    #[cfg(feature = "metrics")]
+    metrics: MetricsSlot,
 }
 
 impl<R> AsyncChild<R> {
     pub fn new(
         action_type: Box<dyn AsyncAction<R>>,
-        status: tokio::sync::watch::Sender<Option<Status>>,
+        id: NodeId,
+        path: String,
+        label: Option<String>,
+        observer: ObserverRef,
     ) -> Self {
         Self {
             action_type,
-            status,
+            id,
+            path,
+            label,
+            status: None,
+            observer,
+            #[cfg(feature = "metrics")]
+            metrics: MetricsSlot::default(),
         }
     }
 
@@ -27,11 +70,37 @@ impl<R> AsyncChild<R> {
         A: AsyncActionName + 'static,
         R: AsyncActionRunner<A> + 'static,
     {
-        let (child, _state) = Self::from_behavior_with_state(behavior);
-        child
+        Self::from_behavior_with_observer(behavior, &None, &MetricsRef::default(), &mut 0)
     }
 
-    pub fn from_behavior_with_state<A>(behavior: Behavior<A>) -> (Self, State)
+    /// Node ids are handed out from `next_id`, children before their parent
+    ///
+    /// Every node adds its counters to `metrics`, replacing any node with the same path.
+    pub fn from_behavior_with_observer<A>(
+        behavior: Behavior<A>,
+        observer: &ObserverRef,
+        metrics: &MetricsRef,
+        next_id: &mut usize,
+    ) -> Self
+    where
+        A: AsyncActionName + 'static,
+        R: AsyncActionRunner<A> + 'static,
+    {
+        Self::from_behavior_at(behavior, observer, metrics, next_id, ROOT, None, None)
+    }
+
+    /// Builds the node attached to `parent` at `index`, see `child_path`
+    ///
+    /// `label` is the display label of the node, set by `Behavior::Named`.
+    fn from_behavior_at<A>(
+        behavior: Behavior<A>,
+        observer: &ObserverRef,
+        metrics: &MetricsRef,
+        next_id: &mut usize,
+        parent: &str,
+        index: Option<usize>,
+        label: Option<String>,
+    ) -> Self
     where
         A: AsyncActionName + 'static,
         R: AsyncActionRunner<A> + 'static,
@@ -39,77 +108,222 @@ impl<R> AsyncChild<R> {
         match behavior {
             Behavior::Action(action) => {
                 let action: Box<dyn AsyncAction<R>> = Box::new(AsyncActionState::new(action));
-
-                let (tx, rx) = tokio::sync::watch::channel(None);
-
-                let state = State::NoChild(action.name(), rx);
-                (Self::new(action, tx), state)
+                let path = child_path(parent, index, action.name());
+                let children = NodeChildren::None;
+                Self::add(action, children, observer, metrics, next_id, path, label)
             }
             Behavior::Wait(target) => {
                 let action: Box<dyn AsyncAction<R>> = Box::new(AsyncWaitState::new(target));
-
-                let (tx, rx) = tokio::sync::watch::channel(None);
-
-                let state = State::NoChild(action.name(), rx);
-                (Self::new(action, tx), state)
+                let path = child_path(parent, index, action.name());
+                let children = NodeChildren::None;
+                Self::add(action, children, observer, metrics, next_id, path, label)
             }
             Behavior::Invert(child) => {
-                let (child, child_state) = Self::from_behavior_with_state(*child);
+                let path = child_path(parent, index, "Invert");
+                let child =
+                    Self::from_behavior_at(*child, observer, metrics, next_id, &path, None, None);
+                let child_id = child.id;
 
                 let action = Box::new(AsyncInvertState::new(child));
-
-                let (tx, rx) = tokio::sync::watch::channel(None);
-
-                let state = State::SingleChild(action.name(), rx, child_state.into());
-                (Self::new(action, tx), state)
+                Self::add(
+                    action,
+                    NodeChildren::Single(child_id),
+                    observer,
+                    metrics,
+                    next_id,
+                    path,
+                    label,
+                )
             }
             Behavior::Sequence(children) => {
-                let (children, children_states): (Vec<_>, Vec<_>) = children
-                    .into_iter()
-                    .map(|child| AsyncChild::from_behavior_with_state(child))
-                    .unzip();
-                let children_states = std::rc::Rc::from_iter(children_states);
+                let path = child_path(parent, index, "Sequence");
+                let children = Self::from_behaviors(children, observer, metrics, next_id, &path);
+                let children_id: Vec<_> = children.iter().map(|child| child.id).collect();
 
                 let action = Box::new(AsyncSequenceState::new(children));
-
-                let (tx, rx) = tokio::sync::watch::channel(None);
-
-                let state = State::MultipleChildren(action.name(), rx, children_states);
-                (Self::new(action, tx), state)
+                Self::add(
+                    action,
+                    NodeChildren::Multiple(&children_id),
+                    observer,
+                    metrics,
+                    next_id,
+                    path,
+                    label,
+                )
             }
             Behavior::Select(children) => {
-                let (children, children_states): (Vec<_>, Vec<_>) = children
-                    .into_iter()
-                    .map(|child| AsyncChild::from_behavior_with_state(child))
-                    .unzip();
-                let children_states = std::rc::Rc::from_iter(children_states);
+                let path = child_path(parent, index, "Select");
+                let children = Self::from_behaviors(children, observer, metrics, next_id, &path);
+                let children_id: Vec<_> = children.iter().map(|child| child.id).collect();
 
                 let action = Box::new(AsyncSelectState::new(children));
+                Self::add(
+                    action,
+                    NodeChildren::Multiple(&children_id),
+                    observer,
+                    metrics,
+                    next_id,
+                    path,
+                    label,
+                )
+            }
+            Behavior::Named(name, behavior) => {
+                // The outermost name wins
+                let label = label.or(Some(name));
+                Self::from_behavior_at(*behavior, observer, metrics, next_id, parent, index, label)
+            }
+        }
+    }
 
-                let (tx, rx) = tokio::sync::watch::channel(None);
+    fn from_behaviors<A>(
+        behaviors: Vec<Behavior<A>>,
+        observer: &ObserverRef,
+        metrics: &MetricsRef,
+        next_id: &mut usize,
+        parent: &str,
+    ) -> Vec<Self>
+    where
+        A: AsyncActionName + 'static,
+        R: AsyncActionRunner<A> + 'static,
+    {
+        behaviors
+            .into_iter()
+            .enumerate()
+            .map(|(index, child)| {
+                Self::from_behavior_at(child, observer, metrics, next_id, parent, Some(index), None)
+            })
+            .collect()
+    }
 
-                let state = State::MultipleChildren(action.name(), rx, children_states);
-                (Self::new(action, tx), state)
-            }
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    fn add(
+        action_type: Box<dyn AsyncAction<R>>,
+        children: NodeChildren<'_>,
+        observer: &ObserverRef,
+        metrics: &MetricsRef,
+        next_id: &mut usize,
+        path: String,
+        label: Option<String>,
+    ) -> Self {
+        let id = NodeId(*next_id);
+        *next_id += 1;
+        if let Some(observer) = observer {
+            observer.borrow_mut().on_node_added(
+                id,
+                action_type.name(),
+                label.as_deref(),
+                &path,
+                children,
+            );
         }
+        let child = Self::new(action_type, id, path, label, observer.clone());
+        #[cfg(feature = "metrics")]
+        metrics
+            .borrow_mut()
+            .insert(child.path.clone(), child.metrics.clone());
+        child
     }
 
+    /// The node span is entered every time the returned future is polled
     pub async fn run(&mut self, delta: tokio::sync::watch::Receiver<f64>, runner: &mut R) -> bool {
-        self.status.send_replace(Some(Status::Running));
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::debug_span!(
+                "node",
+                otel.name = self.label.as_deref().unwrap_or(self.action_type.name()),
+                name = self.action_type.name(),
+                path = %self.path,
+                status = tracing::field::Empty,
+            );
+            let success =
+                tracing::Instrument::instrument(self.run_node(delta, runner), span.clone()).await;
+            let status = if success {
+                Status::Success
+            } else {
+                Status::Failure
+            };
+            span.record("status", tracing::field::debug(status));
+            success
+        }
+        #[cfg(not(feature = "tracing"))]
+        self.run_node(delta, runner).await
+    }
+
+    async fn run_node(&mut self, delta: tokio::sync::watch::Receiver<f64>, runner: &mut R) -> bool {
+        self.set_status(Some(Status::Running));
+        #[cfg(feature = "metrics")]
+        let success = self.run_measured(delta, runner).await;
+        #[cfg(not(feature = "metrics"))]
         let success = self.action_type.run(delta, runner).await;
+
         let status = if success {
             Status::Success
         } else {
             Status::Failure
         };
-        self.status.send_replace(Some(status));
+        #[cfg(feature = "metrics")]
+        {
+            let mut metrics = self.metrics.get();
+            metrics.record(status);
+            self.metrics.set(metrics);
+        }
+        self.set_status(Some(status));
         success
     }
 
+    /// Counts a tick for every frame the node future is polled in, further polls are wakeups
+    #[cfg(feature = "metrics")]
+    async fn run_measured(
+        &mut self,
+        delta: tokio::sync::watch::Receiver<f64>,
+        runner: &mut R,
+    ) -> bool {
+        let mut frame = delta.clone();
+        let mut first_poll = true;
+        let mut future = self.action_type.run(delta, runner);
+        std::future::poll_fn(|cx| {
+            let start = std::time::Instant::now();
+            let poll = future.as_mut().poll(cx);
+
+            let mut metrics = self.metrics.get();
+            metrics.tick_time += start.elapsed();
+            if std::mem::take(&mut first_poll) || frame.has_changed().unwrap_or(false) {
+                let delta = *frame.borrow_and_update();
+                metrics.ticks += 1;
+                // Only a frame that leaves the node running adds its `delta`
+                if poll.is_pending() {
+                    metrics.running_time += delta;
+                }
+            }
+            self.metrics.set(metrics);
+            poll
+        })
+        .await
+    }
+
     pub fn reset(&mut self, runner: &mut R) {
-        self.status.send_replace(None);
+        self.set_status(None);
         self.action_type.reset(runner);
     }
+
+    fn set_status(&mut self, status: Option<Status>) {
+        let old = std::mem::replace(&mut self.status, status);
+        if old != status
+            && let Some(observer) = &self.observer
+        {
+            observer.borrow_mut().on_status_change(self.id, old, status);
+        }
+    }
+}
+
+impl<R> Drop for AsyncChild<R> {
+    fn drop(&mut self) {
+        if let Some(observer) = &self.observer
+            && let Ok(mut observer) = observer.try_borrow_mut()
+        {
+            observer.on_node_removed(self.id);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -117,6 +331,7 @@ mod tests {
     use ticked_async_executor::TickedAsyncExecutor;
 
     use crate::test_async_behavior_interface::{DELTA, TestAction, TestRunner};
+    use crate::test_observer::TestObserver;
 
     use super::*;
 
@@ -144,4 +359,148 @@ mod tests {
         executor.wait_till_completed(DELTA);
         assert_eq!(executor.num_tasks(), 0);
     }
+
+    #[test]
+    fn test_observer_ref() {
+        assert!(observer_ref(&Rc::new(RefCell::new(()))).is_none());
+        assert!(observer_ref(&Rc::new(RefCell::new(TestObserver::default()))).is_some());
+    }
+
+    #[test]
+    fn test_observer() {
+        let behavior =
+            Behavior::Invert(Behavior::Action(TestAction::SuccessAfter { times: 1 }).into());
+
+        let observer = Rc::new(RefCell::new(TestObserver::default()));
+        let mut child = AsyncChild::from_behavior_with_observer(
+            behavior,
+            &observer_ref(&observer),
+            &MetricsRef::default(),
+            &mut 0,
+        );
+        assert_eq!(observer.borrow().names, ["SuccessAfter", "Invert"]);
+
+        let mut executor = TickedAsyncExecutor::default();
+        let mut runner = TestRunner;
+        let delta = executor.tick_channel();
+        executor
+            .spawn_local("InvertFuture", async move {
+                child.run(delta, &mut runner).await;
+                child.reset(&mut runner);
+            })
+            .detach();
+        executor.wait_till_completed(DELTA);
+
+        use Status::*;
+        assert_eq!(
+            observer.borrow().changes,
+            [
+                (1, None, Some(Running)),
+                (0, None, Some(Running)),
+                (0, Some(Running), Some(Success)),
+                (1, Some(Running), Some(Failure)),
+                (1, Some(Failure), None),
+                (0, Some(Success), None),
+            ]
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_spans() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{field::Field, span};
+
+        #[derive(Default)]
+        struct Span {
+            path: String,
+            status: String,
+            parent: Option<u64>,
+        }
+
+        /// Every span in creation order, along with the stack of entered spans
+        #[derive(Clone, Default)]
+        struct Spans(Arc<Mutex<(Vec<Span>, Vec<u64>)>>);
+
+        struct Fields<'a>(&'a mut Span);
+
+        impl tracing::field::Visit for Fields<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                match field.name() {
+                    "path" => self.0.path = format!("{value:?}"),
+                    "status" => self.0.status = format!("{value:?}"),
+                    _ => {}
+                }
+            }
+        }
+
+        impl tracing::Subscriber for Spans {
+            fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+                let (spans, entered) = &mut *self.0.lock().unwrap();
+                let mut fields = Span {
+                    parent: entered.last().copied(),
+                    ..Default::default()
+                };
+                span.record(&mut Fields(&mut fields));
+                spans.push(fields);
+                span::Id::from_u64(spans.len() as u64)
+            }
+
+            fn record(&self, span: &span::Id, values: &span::Record<'_>) {
+                let (spans, _) = &mut *self.0.lock().unwrap();
+                values.record(&mut Fields(&mut spans[span.into_u64() as usize - 1]));
+            }
+
+            fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+            fn event(&self, _event: &tracing::Event<'_>) {}
+
+            fn enter(&self, span: &span::Id) {
+                self.0.lock().unwrap().1.push(span.into_u64());
+            }
+
+            fn exit(&self, span: &span::Id) {
+                let (_, entered) = &mut *self.0.lock().unwrap();
+                assert_eq!(entered.pop(), Some(span.into_u64()));
+            }
+        }
+
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Wait(2.0),
+            Behavior::Invert(Behavior::Action(TestAction::SuccessAfter { times: 1 }).into()),
+        ]);
+        let mut child = AsyncChild::from_behavior(behavior);
+
+        let spans = Spans::default();
+        let _guard = tracing::subscriber::set_default(spans.clone());
+        let mut executor = TickedAsyncExecutor::default();
+        let mut runner = TestRunner;
+        let delta = executor.tick_channel();
+        executor
+            .spawn_local("SequenceFuture", async move {
+                child.run(delta, &mut runner).await;
+            })
+            .detach();
+        executor.wait_till_completed(DELTA);
+
+        let (spans, entered) = &*spans.0.lock().unwrap();
+        assert!(entered.is_empty());
+        let spans: Vec<_> = spans
+            .iter()
+            .map(|span| (span.path.as_str(), span.status.as_str(), span.parent))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                ("root/Sequence", "Failure", None),
+                ("root/Sequence[0]/Wait", "Success", Some(1)),
+                ("root/Sequence[1]/Invert", "Failure", Some(1)),
+                ("root/Sequence[1]/Invert/SuccessAfter", "Success", Some(3)),
+            ]
+        );
+    }
 }
@@ -26,7 +26,7 @@ where
         runner.reset(&self.action);
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         self.action.name()
     }
 }
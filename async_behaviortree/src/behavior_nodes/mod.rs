@@ -14,8 +14,8 @@ pub trait AsyncAction<R> {
     /// Resets the current action to its initial/newly created state
     fn reset(&mut self, runner: &mut R);
 
-    /// Identify your action
-    fn name(&self) -> &'static str;
+    /// Identify your action, may borrow an owned name from the action
+    fn name(&self) -> &str;
 }
 
 // Leaf
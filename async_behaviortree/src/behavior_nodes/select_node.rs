@@ -24,7 +24,7 @@ impl<R> AsyncAction<R> for AsyncSelectState<R> {
             false => {}
         }
         let mut status = false;
-        let last = self.children.len() - 1;
+        let len = self.children.len();
         for (index, child) in self.children.iter_mut().enumerate() {
             let child_status = child.run(delta.clone(), runner).await;
             if child_status {
@@ -34,7 +34,7 @@ impl<R> AsyncAction<R> for AsyncSelectState<R> {
             // Only one child should be run per tick
             // This means that if they are more children after the current child,
             // we must yield back to the executor
-            if index != last {
+            if index + 1 < len {
                 yield_now().await;
             }
         }
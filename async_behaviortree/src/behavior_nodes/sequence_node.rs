@@ -26,7 +26,7 @@ impl<R> AsyncAction<R> for AsyncSequenceState<R> {
             false => {}
         }
         let mut status = true;
-        let last = self.children.len() - 1;
+        let len = self.children.len();
         for (index, child) in self.children.iter_mut().enumerate() {
             let child_status = child.run(delta.clone(), runner).await;
             if !child_status {
@@ -36,7 +36,7 @@ impl<R> AsyncAction<R> for AsyncSequenceState<R> {
             // Only one child should be run per tick
             // This means that if they are more children after the current child,
             // we must yield back to the executor
-            if index != last {
+            if index + 1 < len {
                 yield_now().await;
             }
         }
@@ -89,6 +89,27 @@ mod tests {
         assert_eq!(executor.num_tasks(), 0);
     }
 
+    #[test]
+    fn test_sequence_empty() {
+        let behavior = Behavior::<TestAction>::Sequence(vec![]);
+        let mut sequence = AsyncChild::from_behavior(behavior);
+
+        let mut executor = TickedAsyncExecutor::default();
+
+        let delta = executor.tick_channel();
+        let mut runner = TestRunner;
+
+        executor
+            .spawn_local("SequenceFuture", async move {
+                let status = sequence.run(delta, &mut runner).await;
+                assert!(status);
+            })
+            .detach();
+
+        executor.tick(DELTA, None);
+        assert_eq!(executor.num_tasks(), 0);
+    }
+
     #[test]
     fn test_sequence_failure() {
         let behavior = Behavior::Sequence(vec![Behavior::Action(TestAction::Failure)]);
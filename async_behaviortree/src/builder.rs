@@ -0,0 +1,268 @@
+use crate::{
+    Behavior, BuildError,
+    behavior::is_valid_wait,
+    path::{ROOT, child_path},
+};
+
+/// Builds a [`Behavior`] tree programmatically.
+///
+/// ```
+/// use async_behaviortree::{Behavior, BehaviorBuilder};
+///
+/// let behavior = BehaviorBuilder::new()
+///     .sequence(|b| b.invert(|b| b.action("Fail")).wait(10.0))
+///     .build()
+///     .unwrap();
+///
+/// assert!(
+///     behavior
+///         == Behavior::Sequence(vec![
+///             Behavior::Invert(Box::new(Behavior::Action("Fail"))),
+///             Behavior::Wait(10.0),
+///         ])
+/// );
+/// ```
+pub struct BehaviorBuilder<A> {
+    /// Path of the node that owns `children`
+    path: String,
+    /// Children are indexed when they belong to a control node
+    indexed: bool,
+    /// Number of children added so far, including the invalid ones
+    count: usize,
+    children: Vec<Behavior<A>>,
+    errors: Vec<BuildError>,
+}
+
+impl<A> Default for BehaviorBuilder<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> BehaviorBuilder<A> {
+    pub fn new() -> Self {
+        Self::scope(String::from(ROOT), false)
+    }
+
+    fn scope(path: String, indexed: bool) -> Self {
+        Self {
+            path,
+            indexed,
+            count: 0,
+            children: vec![],
+            errors: vec![],
+        }
+    }
+
+    /// Adds `Behavior::Action`
+    pub fn action(mut self, action: A) -> Self {
+        self.push(Behavior::Action(action));
+        self
+    }
+
+    /// Adds `Behavior::Wait`
+    ///
+    /// target: Time in milliseconds
+    pub fn wait(mut self, target: f64) -> Self {
+        if !is_valid_wait(target) {
+            let path = self.next_path("Wait");
+            self.errors.push(BuildError::InvalidWait { path, target });
+        }
+        self.push(Behavior::Wait(target));
+        self
+    }
+
+    /// Adds `Behavior::Invert` around the single child added by `f`
+    pub fn invert(mut self, f: impl FnOnce(Self) -> Self) -> Self {
+        let path = self.next_path("Invert");
+        let mut builder = f(Self::scope(path, false));
+        self.errors.append(&mut builder.errors);
+
+        if builder.count == 1 {
+            if let Some(child) = builder.children.pop() {
+                self.push(Behavior::Invert(Box::new(child)));
+                return self;
+            }
+        } else {
+            self.errors.push(BuildError::DecoratorChildren {
+                path: builder.path,
+                found: builder.count,
+            });
+        }
+        self.count += 1;
+        self
+    }
+
+    /// Adds `Behavior::Sequence` with the children added by `f`
+    pub fn sequence(self, f: impl FnOnce(Self) -> Self) -> Self {
+        self.control("Sequence", f, Behavior::Sequence)
+    }
+
+    /// Adds `Behavior::Select` with the children added by `f`
+    pub fn select(self, f: impl FnOnce(Self) -> Self) -> Self {
+        self.control("Select", f, Behavior::Select)
+    }
+
+    /// Returns the root behavior, or every problem found while building it
+    pub fn build(mut self) -> Result<Behavior<A>, Vec<BuildError>> {
+        if self.count != 1 {
+            self.errors
+                .insert(0, BuildError::RootChildren { found: self.count });
+        }
+        match self.children.pop() {
+            Some(root) if self.errors.is_empty() => Ok(root),
+            _ => Err(self.errors),
+        }
+    }
+
+    fn control(
+        mut self,
+        name: &'static str,
+        f: impl FnOnce(Self) -> Self,
+        behavior: fn(Vec<Behavior<A>>) -> Behavior<A>,
+    ) -> Self {
+        let path = self.next_path(name);
+        let mut builder = f(Self::scope(path, true));
+        self.errors.append(&mut builder.errors);
+
+        if builder.count == 0 {
+            self.errors
+                .push(BuildError::EmptyComposite { path: builder.path });
+        }
+        self.push(behavior(builder.children));
+        self
+    }
+
+    fn next_path(&self, name: &str) -> String {
+        let index = self.indexed.then_some(self.count);
+        child_path(&self.path, index, name)
+    }
+
+    fn push(&mut self, behavior: Behavior<A>) {
+        self.children.push(behavior);
+        self.count += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let behavior = BehaviorBuilder::new()
+            .select(|b| {
+                b.sequence(|b| b.action(1).invert(|b| b.action(2)))
+                    .wait(10.0)
+            })
+            .build()
+            .unwrap();
+
+        let expected = Behavior::Select(vec![
+            Behavior::Sequence(vec![
+                Behavior::Action(1),
+                Behavior::Invert(Box::new(Behavior::Action(2))),
+            ]),
+            Behavior::Wait(10.0),
+        ]);
+        assert!(behavior == expected);
+    }
+
+    #[test]
+    fn test_builder_generated() {
+        let waypoints = [1, 2, 3];
+        let behavior = BehaviorBuilder::new()
+            .sequence(|b| {
+                waypoints
+                    .iter()
+                    .fold(b, |b, waypoint| b.action(*waypoint).wait(1.0))
+            })
+            .build()
+            .unwrap();
+
+        let expected = Behavior::Sequence(vec![
+            Behavior::Action(1),
+            Behavior::Wait(1.0),
+            Behavior::Action(2),
+            Behavior::Wait(1.0),
+            Behavior::Action(3),
+            Behavior::Wait(1.0),
+        ]);
+        assert!(behavior == expected);
+    }
+
+    #[test]
+    fn test_builder_empty_composite() {
+        let errors = BehaviorBuilder::<u32>::new()
+            .sequence(|b| b.action(1).invert(|b| b.select(|b| b)).sequence(|b| b))
+            .build()
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            errors,
+            [
+                BuildError::EmptyComposite {
+                    path: "root/Sequence[1]/Invert/Select".into()
+                },
+                BuildError::EmptyComposite {
+                    path: "root/Sequence[2]/Sequence".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builder_decorator_children() {
+        let errors = BehaviorBuilder::new()
+            .sequence(|b| b.invert(|b| b).invert(|b| b.action(1).action(2)))
+            .build()
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            errors,
+            [
+                BuildError::DecoratorChildren {
+                    path: "root/Sequence[0]/Invert".into(),
+                    found: 0
+                },
+                BuildError::DecoratorChildren {
+                    path: "root/Sequence[1]/Invert".into(),
+                    found: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builder_invalid_wait() {
+        let errors = BehaviorBuilder::<u32>::new()
+            .select(|b| b.wait(1.0).wait(-1.0))
+            .build()
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            errors,
+            [BuildError::InvalidWait {
+                path: "root/Select[1]/Wait".into(),
+                target: -1.0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_builder_root_children() {
+        let errors = BehaviorBuilder::<u32>::new().build().err().unwrap();
+        assert_eq!(errors, [BuildError::RootChildren { found: 0 }]);
+
+        let errors = BehaviorBuilder::new()
+            .action(1)
+            .action(2)
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(errors, [BuildError::RootChildren { found: 2 }]);
+    }
+}
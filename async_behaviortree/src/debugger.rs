@@ -0,0 +1,427 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use futures::future::Either;
+
+use crate::{
+    AsyncActionName, AsyncActionRunner, AsyncBehaviorController, AsyncBehaviorTree, Behavior,
+    NodeChildren, NodeId, Status, TreeObserver,
+};
+
+/// Node of a breakpoint, by id or by path i.e `root/Sequence[1]/Invert/Action`
+///
+/// Breakpoints apply to the nodes of a replaced behavior by path,
+/// id breakpoints follow the path of their node once it has been removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BreakpointNode {
+    Id(NodeId),
+    Path(String),
+}
+
+impl From<NodeId> for BreakpointNode {
+    fn from(value: NodeId) -> Self {
+        Self::Id(value)
+    }
+}
+
+impl From<&str> for BreakpointNode {
+    fn from(value: &str) -> Self {
+        Self::Path(value.into())
+    }
+}
+
+impl From<String> for BreakpointNode {
+    fn from(value: String) -> Self {
+        Self::Path(value)
+    }
+}
+
+/// Status change that triggers a breakpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakOn {
+    /// The node is ticked for the first time since it was created or reset
+    Start,
+    /// The node changes to this status
+    Status(Status),
+    /// Any status change, resets included
+    Change,
+}
+
+impl BreakOn {
+    fn matches(self, old: Option<Status>, new: Option<Status>) -> bool {
+        match self {
+            BreakOn::Start => old.is_none() && new.is_some(),
+            BreakOn::Status(status) => new == Some(status),
+            BreakOn::Change => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BreakpointId(usize);
+
+/// Status change that triggered a breakpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakpointHit {
+    pub breakpoint: BreakpointId,
+    pub node_id: NodeId,
+    pub old: Option<Status>,
+    pub new: Option<Status>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Mode {
+    #[default]
+    Running,
+    Paused,
+    /// Lets the next frame through, then pauses
+    Step,
+}
+
+#[derive(Default)]
+struct Breakpoints {
+    breakpoints: BTreeMap<BreakpointId, (BreakpointNode, BreakOn)>,
+    next_id: usize,
+    /// Paths of the nodes of the tree
+    paths: BTreeMap<NodeId, String>,
+    hits: Vec<BreakpointHit>,
+    mode: Mode,
+    /// Mode before the pending hits paused the tree
+    mode_before_hits: Option<Mode>,
+}
+
+impl Breakpoints {
+    fn add(&mut self, node: BreakpointNode, on: BreakOn) -> BreakpointId {
+        let id = BreakpointId(self.next_id);
+        self.next_id += 1;
+        self.breakpoints.insert(id, (node, on));
+        id
+    }
+
+    /// Hits of the node are dropped, i.e the resets of a replaced behavior,
+    /// along with the pause they caused
+    fn on_node_removed(&mut self, node_id: NodeId) {
+        let Some(path) = self.paths.remove(&node_id) else {
+            return;
+        };
+        for (node, _) in self.breakpoints.values_mut() {
+            if *node == BreakpointNode::Id(node_id) {
+                *node = BreakpointNode::Path(path.clone());
+            }
+        }
+        self.hits.retain(|hit| hit.node_id != node_id);
+        if self.hits.is_empty()
+            && let Some(mode) = self.mode_before_hits.take()
+        {
+            self.mode = mode;
+        }
+    }
+
+    fn on_status_change(&mut self, node_id: NodeId, old: Option<Status>, new: Option<Status>) {
+        let path = self.paths.get(&node_id);
+        for (&breakpoint, (node, on)) in &self.breakpoints {
+            let at_node = match node {
+                BreakpointNode::Id(id) => *id == node_id,
+                BreakpointNode::Path(node_path) => path == Some(node_path),
+            };
+            if at_node && on.matches(old, new) {
+                if self.hits.is_empty() {
+                    self.mode_before_hits = Some(self.mode);
+                }
+                self.hits.push(BreakpointHit {
+                    breakpoint,
+                    node_id,
+                    old,
+                    new,
+                });
+                self.mode = Mode::Paused;
+            }
+        }
+    }
+}
+
+/// Observer of an [`AsyncDebugger`] tree, forwards every call to the wrapped observer
+pub struct DebugObserver<O> {
+    observer: O,
+    breakpoints: Rc<RefCell<Breakpoints>>,
+}
+
+impl<O> DebugObserver<O> {
+    pub fn inner(&self) -> &O {
+        &self.observer
+    }
+}
+
+impl<O: TreeObserver> TreeObserver for DebugObserver<O> {
+    fn on_node_added(
+        &mut self,
+        node_id: NodeId,
+        name: &str,
+        label: Option<&str>,
+        path: &str,
+        children: NodeChildren<'_>,
+    ) {
+        self.breakpoints
+            .borrow_mut()
+            .paths
+            .insert(node_id, path.into());
+        self.observer
+            .on_node_added(node_id, name, label, path, children);
+    }
+
+    fn on_node_removed(&mut self, node_id: NodeId) {
+        self.breakpoints.borrow_mut().on_node_removed(node_id);
+        self.observer.on_node_removed(node_id);
+    }
+
+    fn on_status_change(&mut self, node_id: NodeId, old: Option<Status>, new: Option<Status>) {
+        self.breakpoints
+            .borrow_mut()
+            .on_status_change(node_id, old, new);
+        self.observer.on_status_change(node_id, old, new);
+    }
+}
+
+/// Holds back actions while the tree is paused
+struct DebugRunner<R> {
+    runner: R,
+    breakpoints: Rc<RefCell<Breakpoints>>,
+}
+
+impl<R> DebugRunner<R> {
+    /// Waits for the next frame let through by the debugger
+    async fn suspend(&self, mut delta: tokio::sync::watch::Receiver<f64>) {
+        if self.breakpoints.borrow().mode != Mode::Running {
+            delta.mark_unchanged();
+            let _r = delta.changed().await;
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl<A, R: AsyncActionRunner<A>> AsyncActionRunner<A> for DebugRunner<R> {
+    async fn run(&mut self, delta: tokio::sync::watch::Receiver<f64>, action: &A) -> bool {
+        self.suspend(delta.clone()).await;
+        self.runner.run(delta, action).await
+    }
+
+    fn reset(&mut self, action: &A) {
+        self.runner.reset(action);
+    }
+
+    async fn wait(&mut self, delta: tokio::sync::watch::Receiver<f64>, target: f64) -> bool {
+        self.suspend(delta.clone()).await;
+        self.runner.wait(delta, target).await
+    }
+}
+
+/// Pauses an `AsyncBehaviorTree` on breakpoints, see [`AsyncDebugger::new`].
+///
+/// The tree is suspended cooperatively: actions do not start and
+/// frames of the executor are not forwarded to the tree while it is paused.
+/// Time does not advance for a paused tree.
+#[derive(Clone)]
+pub struct AsyncDebugger {
+    breakpoints: Rc<RefCell<Breakpoints>>,
+}
+
+impl AsyncDebugger {
+    /// Same as [`AsyncBehaviorTree::new`], along with the debugger of the tree
+    #[allow(clippy::new_ret_no_self, clippy::type_complexity)]
+    pub fn new<A, R, O>(
+        behavior: Behavior<A>,
+        should_loop: bool,
+        mut delta: tokio::sync::watch::Receiver<f64>,
+        runner: R,
+        observer: O,
+    ) -> (
+        impl std::future::Future<Output = ()>,
+        AsyncBehaviorController<A, DebugObserver<O>>,
+        AsyncDebugger,
+    )
+    where
+        A: AsyncActionName + 'static,
+        R: AsyncActionRunner<A> + 'static,
+        O: TreeObserver + 'static,
+    {
+        let breakpoints = Rc::new(RefCell::new(Breakpoints::default()));
+        let (frames, frames_rx) = tokio::sync::watch::channel(*delta.borrow());
+        let runner = DebugRunner {
+            runner,
+            breakpoints: breakpoints.clone(),
+        };
+        let observer = DebugObserver {
+            observer,
+            breakpoints: breakpoints.clone(),
+        };
+        let (tree, controller) =
+            AsyncBehaviorTree::new(behavior, should_loop, frames_rx, runner, observer);
+
+        let forward_breakpoints = breakpoints.clone();
+        let forward = async move {
+            while delta.changed().await.is_ok() {
+                let dt = *delta.borrow_and_update();
+                let mut breakpoints = forward_breakpoints.borrow_mut();
+                match breakpoints.mode {
+                    Mode::Running => {}
+                    Mode::Paused => continue,
+                    Mode::Step => breakpoints.mode = Mode::Paused,
+                }
+                frames.send_replace(dt);
+            }
+        };
+        let future = async move {
+            let tree = std::pin::pin!(tree);
+            let forward = std::pin::pin!(forward);
+            // Frames are forwarded before the tree is polled, to be handled within the same tick.
+            // Once the executor has shutdown, the tree stops like it would without a debugger
+            if let Either::Left(((), tree)) = futures::future::select(forward, tree).await {
+                tree.await;
+            }
+        };
+        (future, controller, AsyncDebugger { breakpoints })
+    }
+
+    /// Pauses the tree whenever `node` changes status as described by `on`
+    pub fn add_breakpoint(&self, node: impl Into<BreakpointNode>, on: BreakOn) -> BreakpointId {
+        self.breakpoints.borrow_mut().add(node.into(), on)
+    }
+
+    /// Returns `false` if the breakpoint had already been removed
+    pub fn remove_breakpoint(&self, id: BreakpointId) -> bool {
+        self.breakpoints
+            .borrow_mut()
+            .breakpoints
+            .remove(&id)
+            .is_some()
+    }
+
+    /// Breakpoints hit since the last call, in order
+    pub fn take_hits(&self) -> Vec<BreakpointHit> {
+        let mut breakpoints = self.breakpoints.borrow_mut();
+        breakpoints.mode_before_hits = None;
+        std::mem::take(&mut breakpoints.hits)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.breakpoints.borrow().mode == Mode::Paused
+    }
+
+    pub fn pause(&self) {
+        self.breakpoints.borrow_mut().mode = Mode::Paused;
+    }
+
+    /// Lets the next frame of the executor through, then pauses again
+    pub fn step(&self) {
+        self.breakpoints.borrow_mut().mode = Mode::Step;
+    }
+
+    /// Runs the tree until the next breakpoint
+    pub fn resume(&self) {
+        self.breakpoints.borrow_mut().mode = Mode::Running;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ticked_async_executor::TickedAsyncExecutor;
+
+    use super::*;
+    use crate::StateObserver;
+    use crate::test_async_behavior_interface::{DELTA, TestAction, TestRunner};
+
+    #[test]
+    fn test_async_debugger() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Action(TestAction::SuccessAfter { times: 2 }),
+            Behavior::Action(TestAction::Success),
+        ]);
+        let mut executor = TickedAsyncExecutor::default();
+        let (future, controller, debugger) = AsyncDebugger::new(
+            behavior,
+            false,
+            executor.tick_channel(),
+            TestRunner,
+            StateObserver::default(),
+        );
+        let start = debugger.add_breakpoint("root/Sequence[1]/SuccessAfter", BreakOn::Start);
+        executor.spawn_local("Debugged", future).detach();
+        let status = |id| controller.observer().inner().node_status(NodeId(id));
+
+        executor.tick(DELTA, None);
+        assert!(!debugger.is_paused());
+        executor.tick(DELTA, None);
+        assert!(debugger.is_paused());
+        assert_eq!(
+            debugger.take_hits(),
+            vec![BreakpointHit {
+                breakpoint: start,
+                node_id: NodeId(1),
+                old: None,
+                new: Some(Status::Running),
+            }]
+        );
+
+        // Suspended until stepped
+        for _ in 0..3 {
+            executor.tick(DELTA, None);
+        }
+        assert_eq!(status(1), Some(Status::Running));
+
+        // Every step lets a single frame through
+        debugger.step();
+        executor.tick(DELTA, None);
+        executor.tick(DELTA, None);
+        assert_eq!(status(1), Some(Status::Running));
+        debugger.step();
+        executor.tick(DELTA, None);
+        assert_eq!(status(1), Some(Status::Success));
+        assert_eq!(status(2), None);
+        assert!(debugger.is_paused());
+
+        debugger.resume();
+        executor.tick(DELTA, None);
+        executor.tick(DELTA, None);
+        assert_eq!(executor.num_tasks(), 0);
+        assert!(debugger.take_hits().is_empty());
+    }
+
+    #[test]
+    fn test_async_debugger_replace_behavior() {
+        let behavior = Behavior::Action(TestAction::SuccessAfter { times: 5 });
+        let mut executor = TickedAsyncExecutor::default();
+        let (future, controller, debugger) =
+            AsyncDebugger::new(behavior, false, executor.tick_channel(), TestRunner, ());
+        let change = debugger.add_breakpoint(NodeId(0), BreakOn::Change);
+        executor.spawn_local("Debugged", future).detach();
+        executor.tick(DELTA, None);
+        assert_eq!(debugger.take_hits().len(), 1);
+        debugger.resume();
+
+        // The reset of the old node is not reported, the id breakpoint follows its path
+        let behavior = Behavior::Action(TestAction::SuccessAfter { times: 1 });
+        assert!(controller.replace_behavior(behavior).is_ok());
+        executor.tick(DELTA, None);
+        assert_eq!(
+            debugger.take_hits(),
+            vec![BreakpointHit {
+                breakpoint: change,
+                node_id: NodeId(1),
+                old: None,
+                new: Some(Status::Running),
+            }]
+        );
+        debugger.resume();
+
+        // Dropped hits don't pause the tree
+        let behavior = Behavior::Action(TestAction::Success);
+        assert!(controller.replace_behavior(behavior).is_ok());
+        executor.tick(DELTA, None);
+        assert!(!debugger.is_paused());
+        assert!(debugger.take_hits().is_empty());
+        executor.tick(DELTA, None);
+        assert_eq!(executor.num_tasks(), 0);
+    }
+}
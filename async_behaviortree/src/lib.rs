@@ -1,19 +1,68 @@
+#[macro_use]
+mod macros;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use std::{boxed::Box, vec};
+}
+
 mod behavior;
 pub use behavior::*;
 
+mod build_error;
+pub use build_error::*;
+
+mod builder;
+pub use builder::*;
+
 mod status;
 pub use status::*;
 
 mod state;
 pub use state::*;
 
+mod state_snapshot;
+pub use state_snapshot::*;
+
+mod observer;
+pub use observer::*;
+
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "metrics")]
+pub use metrics::*;
+
 mod async_action_interface;
 pub use async_action_interface::*;
 
 mod async_behaviortree;
 pub use async_behaviortree::*;
 
+mod debugger;
+pub use debugger::*;
+
+#[cfg(feature = "monitor")]
+mod monitor;
+#[cfg(feature = "monitor")]
+pub use monitor::*;
+
+#[cfg(feature = "testing")]
+mod testing;
+#[cfg(feature = "testing")]
+pub use testing::*;
+
+mod file_watcher;
+pub use file_watcher::*;
+
+mod render;
+
+#[cfg(feature = "xml")]
+mod xml;
+#[cfg(feature = "xml")]
+pub use xml::*;
+
 // Not meant to be used externally
 mod async_child;
 mod behavior_nodes;
+mod path;
 mod util;
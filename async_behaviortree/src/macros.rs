@@ -0,0 +1,118 @@
+/// Declares a [`Behavior`](crate::Behavior) tree.
+///
+/// Every node is written as `kind(argument)` for leaves or `kind { children }` for
+/// decorators and controls. Children may optionally be separated by commas.
+///
+/// ```
+/// use async_behaviortree::{Behavior, behavior};
+///
+/// let behavior: Behavior<&str> = behavior! {
+///     sequence {
+///         invert { action("Fail") }
+///         wait(10.0)
+///     }
+/// };
+///
+/// assert!(
+///     behavior
+///         == Behavior::Sequence(vec![
+///             Behavior::Invert(Box::new(Behavior::Action("Fail"))),
+///             Behavior::Wait(10.0),
+///         ])
+/// );
+/// ```
+///
+/// Malformed trees are rejected at compile time, such as a decorator with two children
+///
+/// ```compile_fail
+/// use async_behaviortree::{Behavior, behavior};
+///
+/// let behavior: Behavior<&str> = behavior! {
+///     invert { action("A") action("B") }
+/// };
+/// ```
+///
+/// or a control without any children
+///
+/// ```compile_fail
+/// use async_behaviortree::{Behavior, behavior};
+///
+/// let behavior: Behavior<&str> = behavior! { select {} };
+/// ```
+#[macro_export]
+macro_rules! behavior {
+    // Leaf
+    (action($action:expr)) => {
+        $crate::Behavior::Action($action)
+    };
+    (wait($target:expr)) => {
+        $crate::Behavior::Wait($target)
+    };
+
+    // Decorator
+    (invert { $kind:ident $body:tt $(,)? }) => {
+        $crate::Behavior::Invert($crate::__private::Box::new($crate::behavior!($kind $body)))
+    };
+    (invert $($tokens:tt)*) => {
+        compile_error!("`invert` takes exactly one child, i.e `invert { action(..) }`")
+    };
+
+    // Control
+    (sequence { $($kind:ident $body:tt $(,)?)+ }) => {
+        $crate::Behavior::Sequence($crate::__private::vec![$($crate::behavior!($kind $body)),+])
+    };
+    (sequence $($tokens:tt)*) => {
+        compile_error!("`sequence` takes one or more children, i.e `sequence { action(..) wait(..) }`")
+    };
+    (select { $($kind:ident $body:tt $(,)?)+ }) => {
+        $crate::Behavior::Select($crate::__private::vec![$($crate::behavior!($kind $body)),+])
+    };
+    (select $($tokens:tt)*) => {
+        compile_error!("`select` takes one or more children, i.e `select { action(..) wait(..) }`")
+    };
+
+    ($($tokens:tt)*) => {
+        compile_error!(concat!(
+            "expected one of `action(..)`, `wait(..)`, `invert { .. }`, `sequence { .. }` or `select { .. }`, found `",
+            stringify!($($tokens)*),
+            "`"
+        ))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Behavior;
+
+    #[test]
+    fn test_behavior_leaf() {
+        let action: Behavior<u32> = behavior! { action(1) };
+        assert!(action == Behavior::Action(1));
+
+        let wait: Behavior<u32> = behavior! { wait(10.0) };
+        assert!(wait == Behavior::Wait(10.0));
+    }
+
+    #[test]
+    fn test_behavior_nested() {
+        let target = 20.0;
+        let behavior = behavior! {
+            select {
+                sequence {
+                    invert { action(1 + 1) }
+                    wait(target)
+                }
+                sequence { action(3), action(4), }
+            }
+        };
+
+        let expected = Behavior::Select(vec![
+            Behavior::Sequence(vec![
+                Behavior::Invert(Box::new(Behavior::Action(2))),
+                Behavior::Wait(20.0),
+            ]),
+            Behavior::Sequence(vec![Behavior::Action(3), Behavior::Action(4)]),
+        ]);
+        assert!(behavior == expected);
+    }
+}
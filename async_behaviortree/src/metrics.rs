@@ -0,0 +1,38 @@
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::Status;
+
+/// Counters of a single node, see `AsyncBehaviorController::metrics`
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NodeMetrics {
+    /// Number of frames the node future was polled in
+    pub ticks: u64,
+    pub successes: u64,
+    pub failures: u64,
+    /// Sum of the `delta` of every frame the node was left `Running` in
+    pub running_time: f64,
+    /// Wall-clock time spent polling the node future, children included
+    pub tick_time: Duration,
+}
+
+impl NodeMetrics {
+    pub(crate) fn record(&mut self, status: Status) {
+        match status {
+            Status::Success => self.successes += 1,
+            Status::Failure => self.failures += 1,
+            Status::Running => {}
+        }
+    }
+}
+
+/// Metrics of every node keyed by path, i.e `root/Sequence[1]/Invert/Action`
+pub type Metrics = BTreeMap<String, NodeMetrics>;
+
+/// Counters of a single node, shared by the node and its controller
+pub(crate) type MetricsSlot = Rc<Cell<NodeMetrics>>;
+
+/// Slots of every node keyed by path
+pub(crate) type MetricsRef = Rc<RefCell<BTreeMap<String, MetricsSlot>>>;
@@ -0,0 +1,141 @@
+use std::fmt::Debug;
+
+use super::RenderNode;
+use crate::{Behavior, State, Status};
+
+impl<A: Debug> Behavior<A> {
+    /// Renders the structure of the tree as an indented ASCII tree, one line per node.
+    ///
+    /// Actions are labelled with their `Debug` representation.
+    pub fn to_ascii(&self) -> String {
+        to_ascii(&RenderNode::from_behavior(self), false)
+    }
+}
+
+impl State {
+    /// Renders the current status of the tree as an indented ASCII tree, one line per node.
+    ///
+    /// Every node is prefixed with its status, `[~]` running, `[+]` success,
+    /// `[x]` failure or `[ ]` idle.
+    ///
+    /// ```text
+    /// [~] Select
+    /// |-- [x] Failure
+    /// |-- [~] Running
+    /// `-- [ ] Success
+    /// ```
+    pub fn to_ascii(&self) -> String {
+        to_ascii(&RenderNode::from_state(self), true)
+    }
+}
+
+fn to_ascii(root: &RenderNode, live: bool) -> String {
+    let mut ascii = String::new();
+    write_node(&mut ascii, root, live, &mut String::new(), None);
+    ascii
+}
+
+/// Writes `node` and its children, `prefix` holds the guides of the parent nodes
+///
+/// `last` is `None` for the root, which has no guide of its own.
+fn write_node(
+    ascii: &mut String,
+    node: &RenderNode,
+    live: bool,
+    prefix: &mut String,
+    last: Option<bool>,
+) {
+    ascii.push_str(prefix);
+    match last {
+        Some(false) => ascii.push_str("|-- "),
+        Some(true) => ascii.push_str("`-- "),
+        None => {}
+    }
+    if live {
+        ascii.push_str(glyph(node.status));
+        ascii.push(' ');
+    }
+    ascii.push_str(&node.label);
+    ascii.push('\n');
+
+    let len = prefix.len();
+    match last {
+        Some(false) => prefix.push_str("|   "),
+        Some(true) => prefix.push_str("    "),
+        None => {}
+    }
+    for (index, child) in node.children.iter().enumerate() {
+        let last = index + 1 == node.children.len();
+        write_node(ascii, child, live, prefix, Some(last));
+    }
+    prefix.truncate(len);
+}
+
+fn glyph(status: Option<Status>) -> &'static str {
+    match status {
+        Some(Status::Running) => "[~]",
+        Some(Status::Success) => "[+]",
+        Some(Status::Failure) => "[x]",
+        None => "[ ]",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_behavior_to_ascii() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Invert(Box::new(Behavior::Select(vec![
+                Behavior::Action("A"),
+                Behavior::Action("B"),
+            ]))),
+            Behavior::Wait(1.5),
+        ]);
+
+        assert_eq!(
+            behavior.to_ascii(),
+            r#"Sequence
+|-- Invert
+|   `-- Select
+|       |-- "A"
+|       `-- "B"
+`-- Wait(1.5)
+"#
+        );
+    }
+
+    #[test]
+    fn test_state_to_ascii() {
+        use ticked_async_executor::TickedAsyncExecutor;
+
+        use crate::test_async_behavior_interface::{DELTA, TestAction, TestRunner};
+        use crate::{AsyncBehaviorTree, StateObserver};
+
+        let behavior = Behavior::Select(vec![
+            Behavior::Action(TestAction::Failure),
+            Behavior::Action(TestAction::SuccessAfter { times: 1 }),
+            Behavior::Action(TestAction::Success),
+        ]);
+        let mut executor = TickedAsyncExecutor::default();
+        let (future, controller) = AsyncBehaviorTree::new(
+            behavior,
+            false,
+            executor.tick_channel(),
+            TestRunner,
+            StateObserver::default(),
+        );
+        executor.spawn_local("AsyncBehaviorTree", future).detach();
+        executor.tick(DELTA, None);
+
+        assert_eq!(
+            controller.state().to_ascii(),
+            r#"[~] Select
+|-- [x] Failure
+|-- [ ] SuccessAfter
+`-- [ ] Success
+"#
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::fmt::{Debug, Write};
+
+use super::RenderNode;
+use crate::{Behavior, State, Status};
+
+impl<A: Debug> Behavior<A> {
+    /// Renders the structure of the tree as a Graphviz DOT digraph.
+    ///
+    /// Actions are labelled with their `Debug` representation.
+    pub fn to_dot(&self) -> String {
+        to_dot(&RenderNode::from_behavior(self), false)
+    }
+}
+
+impl State {
+    /// Renders the current status of the tree as a Graphviz DOT digraph.
+    ///
+    /// Nodes are filled in yellow when running, green on success, red on failure
+    /// and grey when they have not been ticked.
+    pub fn to_dot(&self) -> String {
+        to_dot(&RenderNode::from_state(self), true)
+    }
+}
+
+fn to_dot(root: &RenderNode, live: bool) -> String {
+    let mut dot = String::from("digraph {\n");
+    dot += "  node [shape=box, style=\"rounded,filled\", fillcolor=white];\n";
+    write_node(&mut dot, root, live, &mut 0);
+    dot += "}\n";
+    dot
+}
+
+/// Writes `node` and its children, nodes are numbered in pre-order from `next_id`
+fn write_node(dot: &mut String, node: &RenderNode, live: bool, next_id: &mut usize) -> usize {
+    let id = *next_id;
+    *next_id += 1;
+
+    let _ = write!(dot, "  n{id} [label=\"");
+    for c in node.label.chars() {
+        if matches!(c, '"' | '\\') {
+            dot.push('\\');
+        }
+        dot.push(c);
+    }
+    dot.push('"');
+    if live {
+        let _ = write!(dot, ", fillcolor={}", fill_color(node.status));
+    }
+    dot.push_str("];\n");
+
+    for child in &node.children {
+        let child_id = write_node(dot, child, live, next_id);
+        let _ = writeln!(dot, "  n{id} -> n{child_id};");
+    }
+    id
+}
+
+fn fill_color(status: Option<Status>) -> &'static str {
+    match status {
+        Some(Status::Running) => "gold",
+        Some(Status::Success) => "palegreen",
+        Some(Status::Failure) => "salmon",
+        None => "lightgrey",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_behavior_to_dot() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action("Say \"hi\""),
+            Behavior::Invert(Box::new(Behavior::Wait(1.5))),
+        ]);
+
+        assert_eq!(
+            behavior.to_dot(),
+            r#"digraph {
+  node [shape=box, style="rounded,filled", fillcolor=white];
+  n0 [label="Sequence"];
+  n1 [label="\"Say \\\"hi\\\"\""];
+  n0 -> n1;
+  n2 [label="Invert"];
+  n3 [label="Wait(1.5)"];
+  n2 -> n3;
+  n0 -> n2;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_state_to_dot() {
+        use ticked_async_executor::TickedAsyncExecutor;
+
+        use crate::test_async_behavior_interface::{DELTA, TestAction, TestRunner};
+        use crate::{AsyncBehaviorTree, StateObserver};
+
+        let behavior = Behavior::Select(vec![
+            Behavior::Action(TestAction::Failure),
+            Behavior::Action(TestAction::SuccessAfter { times: 1 }),
+            Behavior::Action(TestAction::Success),
+        ]);
+        let mut executor = TickedAsyncExecutor::default();
+        let (future, controller) = AsyncBehaviorTree::new(
+            behavior,
+            false,
+            executor.tick_channel(),
+            TestRunner,
+            StateObserver::default(),
+        );
+        executor.spawn_local("AsyncBehaviorTree", future).detach();
+        executor.tick(DELTA, None);
+
+        assert_eq!(
+            controller.state().to_dot(),
+            r#"digraph {
+  node [shape=box, style="rounded,filled", fillcolor=white];
+  n0 [label="Select", fillcolor=gold];
+  n1 [label="Failure", fillcolor=salmon];
+  n0 -> n1;
+  n2 [label="SuccessAfter", fillcolor=lightgrey];
+  n0 -> n2;
+  n3 [label="Success", fillcolor=lightgrey];
+  n0 -> n3;
+}
+"#
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use std::fmt::{Debug, Write};
+
+use super::RenderNode;
+use crate::{Behavior, State, Status};
+
+impl<A: Debug> Behavior<A> {
+    /// Renders the structure of the tree as a Mermaid flowchart.
+    ///
+    /// Actions are labelled with their `Debug` representation.
+    pub fn to_mermaid(&self) -> String {
+        to_mermaid(&RenderNode::from_behavior(self), false)
+    }
+}
+
+impl State {
+    /// Renders the current status of the tree as a Mermaid flowchart.
+    ///
+    /// Nodes are classed `running`, `success`, `failure` or `idle`.
+    pub fn to_mermaid(&self) -> String {
+        to_mermaid(&RenderNode::from_state(self), true)
+    }
+}
+
+fn to_mermaid(root: &RenderNode, live: bool) -> String {
+    let mut mermaid = String::from("flowchart TD\n");
+    write_node(&mut mermaid, root, live, &mut 0);
+    if live {
+        mermaid += "  classDef running fill:gold\n";
+        mermaid += "  classDef success fill:palegreen\n";
+        mermaid += "  classDef failure fill:salmon\n";
+        mermaid += "  classDef idle fill:lightgrey\n";
+    }
+    mermaid
+}
+
+/// Writes `node` and its children, nodes are numbered in pre-order from `next_id`
+fn write_node(mermaid: &mut String, node: &RenderNode, live: bool, next_id: &mut usize) -> usize {
+    let id = *next_id;
+    *next_id += 1;
+
+    let _ = write!(mermaid, "  n{id}[\"");
+    for c in node.label.chars() {
+        match c {
+            '"' => mermaid.push_str("#quot;"),
+            '<' => mermaid.push_str("#lt;"),
+            '>' => mermaid.push_str("#gt;"),
+            c => mermaid.push(c),
+        }
+    }
+    mermaid.push_str("\"]");
+    if live {
+        let _ = write!(mermaid, ":::{}", class(node.status));
+    }
+    mermaid.push('\n');
+
+    for child in &node.children {
+        let child_id = write_node(mermaid, child, live, next_id);
+        let _ = writeln!(mermaid, "  n{id} --> n{child_id}");
+    }
+    id
+}
+
+fn class(status: Option<Status>) -> &'static str {
+    match status {
+        Some(Status::Running) => "running",
+        Some(Status::Success) => "success",
+        Some(Status::Failure) => "failure",
+        None => "idle",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_behavior_to_mermaid() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action("Say \"hi\""),
+            Behavior::Invert(Box::new(Behavior::Wait(1.5))),
+        ]);
+
+        assert_eq!(
+            behavior.to_mermaid(),
+            r##"flowchart TD
+  n0["Sequence"]
+  n1["#quot;Say \#quot;hi\#quot;#quot;"]
+  n0 --> n1
+  n2["Invert"]
+  n3["Wait(1.5)"]
+  n2 --> n3
+  n0 --> n2
+"##
+        );
+    }
+
+    #[test]
+    fn test_state_to_mermaid() {
+        use crate::test_async_behavior_interface::{TestAction, TestRunner};
+        use crate::{AsyncBehaviorTree, StateObserver};
+
+        let behavior = Behavior::Invert(Box::new(Behavior::Action(TestAction::Success)));
+        let (_future, controller) = AsyncBehaviorTree::new(
+            behavior,
+            false,
+            tokio::sync::watch::channel(0.0).1,
+            TestRunner,
+            StateObserver::default(),
+        );
+        assert_eq!(
+            controller.state().to_mermaid(),
+            r#"flowchart TD
+  n0["Invert"]:::idle
+  n1["Success"]:::idle
+  n0 --> n1
+  classDef running fill:gold
+  classDef success fill:palegreen
+  classDef failure fill:salmon
+  classDef idle fill:lightgrey
+"#
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{NodeId, Status};
+
+/// Plain copy of a `State` tree, i.e to send it to another process.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StateSnapshot {
+    pub id: NodeId,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// i.e `root/Sequence[1]/Invert/Action`
+    pub path: String,
+    pub status: Option<Status>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<StateSnapshot>,
+}
+
+/// Changes between two snapshots of a tree, see [`StateSnapshot::diff`]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum StateDiff {
+    /// Nodes whose status changed, in id order
+    Statuses(Vec<(NodeId, Option<Status>)>),
+    /// The tree has been replaced, i.e by `AsyncBehaviorController::replace_behavior`
+    Tree(StateSnapshot),
+}
+
+impl StateDiff {
+    /// `true` when both snapshots were equal
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Self::Statuses(statuses) if statuses.is_empty())
+    }
+}
+
+impl StateSnapshot {
+    /// Snapshot of the node `id` in this tree
+    pub fn find(&self, id: NodeId) -> Option<&StateSnapshot> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(id))
+    }
+
+    /// Changes turning `self` into `newer`, only statuses are sent while the tree keeps its nodes
+    pub fn diff(&self, newer: &StateSnapshot) -> StateDiff {
+        let mut statuses = Vec::new();
+        if !self.diff_statuses(newer, &mut statuses) {
+            return StateDiff::Tree(newer.clone());
+        }
+        statuses.sort_by_key(|(id, _)| *id);
+        StateDiff::Statuses(statuses)
+    }
+
+    /// Returns `false` when the nodes differ
+    fn diff_statuses(
+        &self,
+        newer: &StateSnapshot,
+        statuses: &mut Vec<(NodeId, Option<Status>)>,
+    ) -> bool {
+        if self.id != newer.id || self.children.len() != newer.children.len() {
+            return false;
+        }
+        if self.status != newer.status {
+            statuses.push((newer.id, newer.status));
+        }
+        self.children
+            .iter()
+            .zip(&newer.children)
+            .all(|(old, new)| old.diff_statuses(new, statuses))
+    }
+
+    /// Applies a diff made by [`StateSnapshot::diff`] on a copy of `self`
+    ///
+    /// Statuses of nodes missing from this tree are ignored.
+    pub fn apply(&mut self, diff: &StateDiff) {
+        match diff {
+            StateDiff::Statuses(statuses) => {
+                for &(id, status) in statuses {
+                    if let Some(node) = self.find_mut(id) {
+                        node.status = status;
+                    }
+                }
+            }
+            StateDiff::Tree(tree) => *self = tree.clone(),
+        }
+    }
+
+    fn find_mut(&mut self, id: NodeId) -> Option<&mut StateSnapshot> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.children
+            .iter_mut()
+            .find_map(|child| child.find_mut(id))
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: usize, name: &str, status: Option<Status>) -> StateSnapshot {
+        StateSnapshot {
+            id: NodeId(id),
+            name: name.into(),
+            label: None,
+            path: name.into(),
+            status,
+            children: vec![],
+        }
+    }
+
+    fn tree(statuses: [Option<Status>; 3]) -> StateSnapshot {
+        StateSnapshot {
+            children: vec![
+                node(0, "Success", statuses[0]),
+                node(1, "Failure", statuses[1]),
+            ],
+            ..node(2, "Sequence", statuses[2])
+        }
+    }
+
+    #[test]
+    fn test_state_snapshot_diff() {
+        let old = tree([None, None, None]);
+        assert!(old.diff(&old).is_empty());
+
+        let new = tree([Some(Status::Success), None, Some(Status::Running)]);
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff,
+            StateDiff::Statuses(vec![
+                (NodeId(0), Some(Status::Success)),
+                (NodeId(2), Some(Status::Running))
+            ])
+        );
+
+        let json = serde_json::to_string(&diff).unwrap();
+        let diff: StateDiff = serde_json::from_str(&json).unwrap();
+        let mut remote = old.clone();
+        remote.apply(&diff);
+        assert_eq!(remote, new);
+
+        let replaced = node(3, "Wait", None);
+        let diff = new.diff(&replaced);
+        assert_eq!(diff, StateDiff::Tree(replaced.clone()));
+        remote.apply(&diff);
+        assert_eq!(remote, replaced);
+    }
+}
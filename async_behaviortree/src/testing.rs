@@ -0,0 +1,272 @@
+//! Scripted actions and a harness to test trees, see [`MockAction`] and [`AsyncTreeHarness`].
+
+use std::collections::BTreeMap;
+
+use ticked_async_executor::{TaskState, TickedAsyncExecutor};
+
+use crate::{
+    AsyncActionName, AsyncActionRunner, AsyncBehaviorController, AsyncBehaviorTree, Behavior,
+    State, StateObserver, StateSnapshot, Status,
+};
+
+/// Action returning a scripted sequence of statuses, one per frame, when run by a [`MockRunner`].
+///
+/// Frames past the end of the script repeat its last status, an empty script keeps running.
+/// The script starts over every time the action is run.
+///
+/// ```
+/// use async_behaviortree::{AsyncTreeHarness, Behavior, MockAction, MockRunner};
+///
+/// // Running x3 then Success
+/// let behavior = Behavior::Action(MockAction::new("MoveTo").running(3).success());
+/// let mut harness = AsyncTreeHarness::new(behavior, false, MockRunner);
+/// harness.run(0.1, 4);
+/// harness.assert_timeline("root/MoveTo", "R R R S");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockAction {
+    name: String,
+    script: Vec<Status>,
+}
+
+impl MockAction {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            script: Vec::new(),
+        }
+    }
+
+    /// Appends `times` frames returning `status`
+    pub fn then(mut self, status: Status, times: usize) -> Self {
+        self.script.extend(std::iter::repeat_n(status, times));
+        self
+    }
+
+    pub fn running(self, times: usize) -> Self {
+        self.then(Status::Running, times)
+    }
+
+    pub fn success(self) -> Self {
+        self.then(Status::Success, 1)
+    }
+
+    pub fn failure(self) -> Self {
+        self.then(Status::Failure, 1)
+    }
+
+    pub fn script(&self) -> &[Status] {
+        &self.script
+    }
+}
+
+impl AsyncActionName for MockAction {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Runs [`MockAction`]s
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MockRunner;
+
+#[async_trait::async_trait(?Send)]
+impl AsyncActionRunner<MockAction> for MockRunner {
+    async fn run(
+        &mut self,
+        mut delta: tokio::sync::watch::Receiver<f64>,
+        action: &MockAction,
+    ) -> bool {
+        // Every step waits for a new frame, starting from the current one
+        delta.borrow_and_update();
+        let script = &action.script;
+        for frame in 0.. {
+            match script.get(frame).or(script.last()) {
+                Some(Status::Success) => return true,
+                Some(Status::Failure) => return false,
+                Some(Status::Running) | None => {
+                    if delta.changed().await.is_err() {
+                        break;
+                    }
+                    delta.borrow_and_update();
+                }
+            }
+        }
+        false
+    }
+
+    fn reset(&mut self, _action: &MockAction) {}
+}
+
+/// Runs an `AsyncBehaviorTree` on a `TickedAsyncExecutor` and records the status of every
+/// node after each tick of the executor.
+///
+/// Timelines are keyed by node path, i.e `root/Sequence[1]/Invert/Action`, and written as
+/// whitespace separated `S`, `F`, `R` or `-` (idle) by [`AsyncTreeHarness::assert_timeline`].
+pub struct AsyncTreeHarness<A> {
+    executor: TickedAsyncExecutor<fn(TaskState)>,
+    controller: AsyncBehaviorController<A, StateObserver>,
+    /// Kept once the tree completes, its nodes are then removed from the observer
+    state: State,
+    replaced: bool,
+    timelines: BTreeMap<String, Vec<Option<Status>>>,
+}
+
+impl<A> AsyncTreeHarness<A> {
+    pub fn new<R>(behavior: Behavior<A>, should_loop: bool, runner: R) -> Self
+    where
+        A: AsyncActionName + 'static,
+        R: AsyncActionRunner<A> + 'static,
+    {
+        let executor = TickedAsyncExecutor::default();
+        let (future, controller) = AsyncBehaviorTree::new(
+            behavior,
+            should_loop,
+            executor.tick_channel(),
+            runner,
+            StateObserver::default(),
+        );
+        executor.spawn_local("AsyncTreeHarness", future).detach();
+        Self {
+            executor,
+            state: controller.state(),
+            controller,
+            replaced: false,
+            timelines: BTreeMap::new(),
+        }
+    }
+
+    /// Ticks the executor once, returns the status of the root node
+    pub fn tick(&mut self, dt: f64) -> Option<Status> {
+        self.executor.tick(dt, None);
+        if std::mem::take(&mut self.replaced) {
+            self.state = self.controller.state();
+        }
+        let snapshot = self.state.snapshot();
+        self.record(&snapshot);
+        snapshot.status
+    }
+
+    fn record(&mut self, node: &StateSnapshot) {
+        self.timelines
+            .entry(node.path.clone())
+            .or_default()
+            .push(node.status);
+        for child in &node.children {
+            self.record(child);
+        }
+    }
+
+    /// Ticks the executor `ticks` times, returns the last status of the root node
+    pub fn run(&mut self, dt: f64, ticks: usize) -> Option<Status> {
+        let mut status = None;
+        for _ in 0..ticks {
+            status = self.tick(dt);
+        }
+        status
+    }
+
+    /// Statuses of the node at `path` after every tick since it was added
+    pub fn timeline(&self, path: &str) -> Option<&[Option<Status>]> {
+        self.timelines.get(path).map(Vec::as_slice)
+    }
+
+    /// Timelines of every node, by path
+    pub fn timelines(&self) -> impl Iterator<Item = (&str, &[Option<Status>])> {
+        self.timelines
+            .iter()
+            .map(|(path, timeline)| (path.as_str(), timeline.as_slice()))
+    }
+
+    /// Panics unless the timeline of the node at `path` is `expected`, i.e `"R R S"`
+    #[track_caller]
+    pub fn assert_timeline(&self, path: &str, expected: &str) {
+        let Some(timeline) = self.timeline(path) else {
+            let paths: Vec<_> = self.timelines.keys().map(String::as_str).collect();
+            panic!("no node at `{path}`, known paths are {paths:?}");
+        };
+        let found = format_timeline(timeline);
+        let expected = format_timeline(&parse_timeline(expected));
+        assert!(
+            found == expected,
+            "timeline of `{path}` differs\n expected: {expected}\n    found: {found}"
+        );
+    }
+
+    /// See [`AsyncBehaviorController::replace_behavior`], the timelines of the new nodes start at the next tick
+    pub fn replace_behavior(&mut self, behavior: Behavior<A>) -> Result<(), Behavior<A>> {
+        self.controller.replace_behavior(behavior)?;
+        self.replaced = true;
+        Ok(())
+    }
+
+    pub fn controller(&self) -> &AsyncBehaviorController<A, StateObserver> {
+        &self.controller
+    }
+}
+
+#[track_caller]
+fn parse_timeline(timeline: &str) -> Vec<Option<Status>> {
+    timeline
+        .split_whitespace()
+        .map(|status| match status {
+            "S" => Some(Status::Success),
+            "F" => Some(Status::Failure),
+            "R" => Some(Status::Running),
+            "-" => None,
+            _ => panic!("unknown status `{status}`, expected S, F, R or -"),
+        })
+        .collect()
+}
+
+fn format_timeline(timeline: &[Option<Status>]) -> String {
+    let statuses: Vec<_> = timeline
+        .iter()
+        .map(|status| match status {
+            Some(Status::Success) => "S",
+            Some(Status::Failure) => "F",
+            Some(Status::Running) => "R",
+            None => "-",
+        })
+        .collect();
+    statuses.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_async_tree_harness() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(MockAction::new("Move").running(2).success()),
+            Behavior::Action(MockAction::new("Say").success()),
+        ]);
+        let mut harness = AsyncTreeHarness::new(behavior, true, MockRunner);
+        assert_eq!(harness.run(0.1, 5), Some(Status::Running));
+
+        harness.assert_timeline("root/Sequence", "R R R S R");
+        harness.assert_timeline("root/Sequence[0]/Move", "R R S S R");
+        harness.assert_timeline("root/Sequence[1]/Say", "- - - S -");
+        assert_eq!(harness.timeline("root/Wait"), None);
+
+        assert!(
+            harness
+                .replace_behavior(Behavior::Action(
+                    MockAction::new("Nap").running(1).failure()
+                ))
+                .is_ok()
+        );
+        assert_eq!(harness.run(0.1, 2), Some(Status::Failure));
+        harness.assert_timeline("root/Nap", "R F");
+    }
+
+    #[test]
+    #[should_panic(expected = "timeline of `root/Move` differs")]
+    fn test_async_tree_harness_mismatch() {
+        let behavior = Behavior::Action(MockAction::new("Move").running(1).success());
+        let mut harness = AsyncTreeHarness::new(behavior, false, MockRunner);
+        harness.run(0.1, 2);
+        harness.assert_timeline("root/Move", "R R");
+    }
+}
@@ -0,0 +1,562 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use roxmltree::{Document, Node};
+
+use crate::{Behavior, behavior::is_valid_wait};
+
+/// A leaf node of a BehaviorTree.CPP document, handed to the action mapping.
+///
+/// `<Action ID="Say"/>` and `<Condition ID="Say"/>` are reported with the `Say` tag.
+/// Attributes are reported after `SubTree` port remapping.
+pub struct XmlLeaf<'a> {
+    tag: &'a str,
+    attributes: Vec<(&'a str, String)>,
+}
+
+impl XmlLeaf<'_> {
+    pub fn tag(&self) -> &str {
+        self.tag
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| *attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.attributes
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+    }
+}
+
+/// A problem found while loading a BehaviorTree.CPP document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlError {
+    pub line: u32,
+    pub column: u32,
+    pub kind: XmlErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmlErrorKind {
+    /// The document is not well-formed XML.
+    Parse(String),
+    /// A node without a `Behavior` equivalent, or a leaf rejected by the action mapping.
+    UnsupportedNode(String),
+    /// A control, decorator or tree with the wrong number of children.
+    InvalidChildren {
+        tag: String,
+        found: usize,
+    },
+    MissingAttribute {
+        tag: String,
+        name: &'static str,
+    },
+    InvalidAttribute {
+        tag: String,
+        name: &'static str,
+        value: String,
+    },
+    /// `main_tree_to_execute` or a `SubTree` refers to an undefined tree.
+    UnknownTree(String),
+    /// A `SubTree` that includes itself.
+    RecursiveTree(String),
+}
+
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        match &self.kind {
+            XmlErrorKind::Parse(message) => write!(f, "{message}"),
+            XmlErrorKind::UnsupportedNode(tag) => write!(f, "unsupported node `{tag}`"),
+            XmlErrorKind::InvalidChildren { tag, found } => {
+                write!(f, "`{tag}` has an invalid number of children: {found}")
+            }
+            XmlErrorKind::MissingAttribute { tag, name } => {
+                write!(f, "`{tag}` is missing the `{name}` attribute")
+            }
+            XmlErrorKind::InvalidAttribute { tag, name, value } => {
+                write!(f, "`{tag}` has an invalid `{name}` attribute: `{value}`")
+            }
+            XmlErrorKind::UnknownTree(id) => write!(f, "unknown tree `{id}`"),
+            XmlErrorKind::RecursiveTree(id) => write!(f, "tree `{id}` includes itself"),
+        }
+    }
+}
+
+impl std::error::Error for XmlError {}
+
+impl<A> Behavior<A> {
+    /// Loads a tree from the [BehaviorTree.CPP](https://www.behaviortree.dev) v4 XML format.
+    ///
+    /// `Sequence`, `Fallback`, `Inverter` and `Sleep` map to `Sequence`, `Select`, `Invert`
+    /// and `Wait`, `SubTree`s are inlined. Every other leaf is handed to `action`,
+    /// returning `None` reports it as unsupported.
+    ///
+    /// ```
+    /// use async_behaviortree::Behavior;
+    ///
+    /// let xml = r#"
+    ///     <root BTCPP_format="4">
+    ///         <BehaviorTree ID="Main">
+    ///             <Fallback>
+    ///                 <Inverter><IsDoorOpen/></Inverter>
+    ///                 <Sleep msec="500"/>
+    ///             </Fallback>
+    ///         </BehaviorTree>
+    ///     </root>
+    /// "#;
+    ///
+    /// let behavior = Behavior::from_btcpp_xml(xml, |leaf| Some(leaf.tag().to_owned())).unwrap();
+    /// assert!(
+    ///     behavior
+    ///         == Behavior::Select(vec![
+    ///             Behavior::Invert(Box::new(Behavior::Action("IsDoorOpen".to_owned()))),
+    ///             Behavior::Wait(500.0),
+    ///         ])
+    /// );
+    /// ```
+    pub fn from_btcpp_xml(
+        xml: &str,
+        action: impl FnMut(&XmlLeaf<'_>) -> Option<A>,
+    ) -> Result<Self, Vec<XmlError>> {
+        let doc = Document::parse(xml).map_err(|error| {
+            let pos = error.pos();
+            vec![XmlError {
+                line: pos.row,
+                column: pos.col,
+                kind: XmlErrorKind::Parse(error.to_string()),
+            }]
+        })?;
+
+        let mut loader = Loader {
+            doc: &doc,
+            trees: BTreeMap::new(),
+            action,
+            errors: vec![],
+            stack: vec![],
+        };
+        let behavior = loader.load_root(doc.root_element());
+        match behavior {
+            Some(behavior) if loader.errors.is_empty() => Ok(behavior),
+            _ => Err(loader.errors),
+        }
+    }
+}
+
+/// Port name to value, as remapped by the enclosing `SubTree`s
+type Ports = BTreeMap<String, String>;
+
+struct Loader<'a, 'input, F> {
+    doc: &'a Document<'input>,
+    trees: BTreeMap<&'a str, Node<'a, 'input>>,
+    action: F,
+    errors: Vec<XmlError>,
+    /// Ids of the trees being inlined, to catch recursive `SubTree`s
+    stack: Vec<&'a str>,
+}
+
+impl<'a, 'input, A, F> Loader<'a, 'input, F>
+where
+    F: FnMut(&XmlLeaf<'_>) -> Option<A>,
+{
+    fn load_root(&mut self, root: Node<'a, 'input>) -> Option<Behavior<A>> {
+        if root.tag_name().name() != "root" {
+            self.error(root, XmlErrorKind::UnsupportedNode(tag(root)));
+            return None;
+        }
+
+        for node in root.children().filter(Node::is_element) {
+            match node.tag_name().name() {
+                "BehaviorTree" => match node.attribute("ID") {
+                    Some(id) => {
+                        self.trees.insert(id, node);
+                    }
+                    None => self.missing_attribute(node, "ID"),
+                },
+                "TreeNodesModel" => {}
+                _ => self.error(node, XmlErrorKind::UnsupportedNode(tag(node))),
+            }
+        }
+
+        let main = match root.attribute("main_tree_to_execute") {
+            Some(id) => id,
+            None if self.trees.len() == 1 => self.trees.keys().next().copied()?,
+            None => {
+                self.missing_attribute(root, "main_tree_to_execute");
+                return None;
+            }
+        };
+        self.load_tree(root, main, &Ports::new())
+    }
+
+    fn load_tree(
+        &mut self,
+        from: Node<'a, 'input>,
+        id: &str,
+        ports: &Ports,
+    ) -> Option<Behavior<A>> {
+        let Some((id, tree)) = self.trees.get_key_value(id).map(|(id, tree)| (*id, *tree)) else {
+            self.error(from, XmlErrorKind::UnknownTree(id.to_owned()));
+            return None;
+        };
+        if self.stack.contains(&id) {
+            self.error(from, XmlErrorKind::RecursiveTree(id.to_owned()));
+            return None;
+        }
+
+        let children: Vec<_> = tree.children().filter(Node::is_element).collect();
+        let [child] = children[..] else {
+            self.invalid_children(tree, children.len());
+            return None;
+        };
+
+        self.stack.push(id);
+        let behavior = self.load_node(child, ports);
+        self.stack.pop();
+        behavior
+    }
+
+    /// Built-in nodes with a `name` attribute are wrapped in `Behavior::Named`,
+    /// leaves keep it among their attributes.
+    fn load_node(&mut self, node: Node<'a, 'input>, ports: &Ports) -> Option<Behavior<A>> {
+        let behavior = self.load_unnamed_node(node, ports)?;
+        match node.attribute("name") {
+            Some(name)
+                if matches!(
+                    node.tag_name().name(),
+                    "Sequence" | "Fallback" | "Inverter" | "Sleep"
+                ) =>
+            {
+                Some(Behavior::Named(resolve(name, ports), Box::new(behavior)))
+            }
+            _ => Some(behavior),
+        }
+    }
+
+    fn load_unnamed_node(&mut self, node: Node<'a, 'input>, ports: &Ports) -> Option<Behavior<A>> {
+        let children: Vec<_> = node.children().filter(Node::is_element).collect();
+        match (node.tag_name().name(), children.len()) {
+            ("Sequence" | "Fallback", 0) => {
+                self.invalid_children(node, 0);
+                None
+            }
+            ("Inverter", found) if found != 1 => {
+                self.invalid_children(node, found);
+                None
+            }
+            ("Sequence" | "Fallback", _) => {
+                // Load every child to report all of their errors
+                let children: Vec<_> = children
+                    .into_iter()
+                    .map(|child| self.load_node(child, ports))
+                    .collect();
+                let children = children.into_iter().collect::<Option<Vec<_>>>()?;
+                match node.tag_name().name() {
+                    "Sequence" => Some(Behavior::Sequence(children)),
+                    _ => Some(Behavior::Select(children)),
+                }
+            }
+            ("Inverter", _) => {
+                let child = self.load_node(children[0], ports)?;
+                Some(Behavior::Invert(Box::new(child)))
+            }
+            ("SubTree", 0) => {
+                let Some(id) = node.attribute("ID") else {
+                    self.missing_attribute(node, "ID");
+                    return None;
+                };
+
+                let autoremap = node.attribute("_autoremap") == Some("true");
+                let mut subtree_ports = if autoremap {
+                    ports.clone()
+                } else {
+                    Ports::new()
+                };
+                for attribute in node.attributes() {
+                    let name = attribute.name();
+                    if matches!(name, "ID" | "name" | "_autoremap") {
+                        continue;
+                    }
+                    let value = match attribute.value() {
+                        "{=}" => ["{", name, "}"].concat(),
+                        value => value.to_owned(),
+                    };
+                    subtree_ports.insert(name.to_owned(), resolve(&value, ports));
+                }
+                self.load_tree(node, id, &subtree_ports)
+            }
+            ("Sleep", 0) => {
+                let Some(msec) = node.attribute("msec") else {
+                    self.missing_attribute(node, "msec");
+                    return None;
+                };
+                let msec = resolve(msec, ports);
+                // BT.CPP sleeps an unsigned number of milliseconds
+                match msec.parse::<f64>() {
+                    Ok(target) if target.is_finite() && is_valid_wait(target) => {
+                        Some(Behavior::Wait(target))
+                    }
+                    _ => {
+                        self.error(
+                            node,
+                            XmlErrorKind::InvalidAttribute {
+                                tag: tag(node),
+                                name: "msec",
+                                value: msec,
+                            },
+                        );
+                        None
+                    }
+                }
+            }
+            (name, 0) => {
+                let id = match name {
+                    "Action" | "Condition" => node.attribute("ID"),
+                    _ => None,
+                };
+                let leaf = XmlLeaf {
+                    tag: id.unwrap_or(name),
+                    attributes: node
+                        .attributes()
+                        .filter(|attribute| id.is_none() || attribute.name() != "ID")
+                        .map(|attribute| (attribute.name(), resolve(attribute.value(), ports)))
+                        .collect(),
+                };
+                let action = (self.action)(&leaf);
+                if action.is_none() {
+                    self.error(node, XmlErrorKind::UnsupportedNode(leaf.tag.to_owned()));
+                }
+                action.map(Behavior::Action)
+            }
+            _ => {
+                self.error(node, XmlErrorKind::UnsupportedNode(tag(node)));
+                None
+            }
+        }
+    }
+
+    fn invalid_children(&mut self, node: Node<'_, '_>, found: usize) {
+        let tag = tag(node);
+        self.error(node, XmlErrorKind::InvalidChildren { tag, found });
+    }
+
+    fn missing_attribute(&mut self, node: Node<'_, '_>, name: &'static str) {
+        let tag = tag(node);
+        self.error(node, XmlErrorKind::MissingAttribute { tag, name });
+    }
+
+    fn error(&mut self, node: Node<'_, '_>, kind: XmlErrorKind) {
+        let pos = self.doc.text_pos_at(node.range().start);
+        self.errors.push(XmlError {
+            line: pos.row,
+            column: pos.col,
+            kind,
+        });
+    }
+}
+
+fn tag(node: Node<'_, '_>) -> String {
+    node.tag_name().name().to_owned()
+}
+
+/// Replaces a `{port}` value with the value remapped by the enclosing `SubTree`
+fn resolve(value: &str, ports: &Ports) -> String {
+    value
+        .strip_prefix('{')
+        .and_then(|port| port.strip_suffix('}'))
+        .and_then(|port| ports.get(port))
+        .cloned()
+        .unwrap_or_else(|| value.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(xml: &str) -> Result<Behavior<String>, Vec<XmlError>> {
+        Behavior::from_btcpp_xml(xml, |leaf| {
+            let mut action = leaf.tag().to_owned();
+            for (name, value) in leaf.attributes() {
+                action += &format!(" {name}={value}");
+            }
+            (leaf.tag() != "Unknown").then_some(action)
+        })
+    }
+
+    #[test]
+    fn test_from_btcpp_xml() {
+        let xml = r#"
+            <root BTCPP_format="4" main_tree_to_execute="Main">
+                <BehaviorTree ID="Main">
+                    <Sequence name="root">
+                        <Action ID="Say" message="hello"/>
+                        <Fallback>
+                            <Inverter><IsDoorOpen/></Inverter>
+                            <Sleep msec="500"/>
+                        </Fallback>
+                        <SubTree ID="Move" target="{goal}"/>
+                    </Sequence>
+                </BehaviorTree>
+                <BehaviorTree ID="Move">
+                    <MoveBase goal="{target}" speed="1.5"/>
+                </BehaviorTree>
+                <TreeNodesModel>
+                    <Action ID="Say"><input_port name="message"/></Action>
+                </TreeNodesModel>
+            </root>
+        "#;
+
+        let behavior = load(xml).unwrap();
+        let expected = Behavior::Named(
+            "root".to_owned(),
+            Box::new(Behavior::Sequence(vec![
+                Behavior::Action("Say message=hello".to_owned()),
+                Behavior::Select(vec![
+                    Behavior::Invert(Box::new(Behavior::Action("IsDoorOpen".to_owned()))),
+                    Behavior::Wait(500.0),
+                ]),
+                Behavior::Action("MoveBase goal={goal} speed=1.5".to_owned()),
+            ])),
+        );
+        assert!(behavior == expected);
+    }
+
+    #[test]
+    fn test_from_btcpp_xml_subtree_ports() {
+        let xml = r#"
+            <root main_tree_to_execute="Main">
+                <BehaviorTree ID="Main">
+                    <SubTree ID="Outer" goal="kitchen" delay="250"/>
+                </BehaviorTree>
+                <BehaviorTree ID="Outer">
+                    <SubTree ID="Inner" target="{goal}" speed="{=}" _autoremap="true"/>
+                </BehaviorTree>
+                <BehaviorTree ID="Inner">
+                    <Sequence>
+                        <MoveBase goal="{target}" speed="{speed}"/>
+                        <Sleep msec="{delay}"/>
+                    </Sequence>
+                </BehaviorTree>
+            </root>
+        "#;
+
+        let behavior = load(xml).unwrap();
+        let expected = Behavior::Sequence(vec![
+            Behavior::Action("MoveBase goal=kitchen speed={speed}".to_owned()),
+            Behavior::Wait(250.0),
+        ]);
+        assert!(behavior == expected);
+    }
+
+    #[test]
+    fn test_from_btcpp_xml_errors() {
+        let xml = r#"<root BTCPP_format="4">
+    <BehaviorTree ID="Main">
+        <Sequence>
+            <Parallel><Say/></Parallel>
+            <Unknown/>
+            <Inverter/>
+            <Fallback/>
+            <SubTree ID="Missing"/>
+            <SubTree ID="Main"/>
+            <Sleep msec="soon"/>
+            <Sleep msec="-5"/>
+            <Sleep msec="NaN"/>
+        </Sequence>
+    </BehaviorTree>
+</root>"#;
+
+        let errors = load(xml).err().unwrap();
+        let errors: Vec<_> = errors
+            .iter()
+            .map(|error| (error.line, error.column, error.kind.clone()))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (4, 13, XmlErrorKind::UnsupportedNode("Parallel".to_owned())),
+                (5, 13, XmlErrorKind::UnsupportedNode("Unknown".to_owned())),
+                (
+                    6,
+                    13,
+                    XmlErrorKind::InvalidChildren {
+                        tag: "Inverter".to_owned(),
+                        found: 0
+                    }
+                ),
+                (
+                    7,
+                    13,
+                    XmlErrorKind::InvalidChildren {
+                        tag: "Fallback".to_owned(),
+                        found: 0
+                    }
+                ),
+                (8, 13, XmlErrorKind::UnknownTree("Missing".to_owned())),
+                (9, 13, XmlErrorKind::RecursiveTree("Main".to_owned())),
+                (
+                    10,
+                    13,
+                    XmlErrorKind::InvalidAttribute {
+                        tag: "Sleep".to_owned(),
+                        name: "msec",
+                        value: "soon".to_owned()
+                    }
+                ),
+                (
+                    11,
+                    13,
+                    XmlErrorKind::InvalidAttribute {
+                        tag: "Sleep".to_owned(),
+                        name: "msec",
+                        value: "-5".to_owned()
+                    }
+                ),
+                (
+                    12,
+                    13,
+                    XmlErrorKind::InvalidAttribute {
+                        tag: "Sleep".to_owned(),
+                        name: "msec",
+                        value: "NaN".to_owned()
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            load(xml).err().unwrap()[0].to_string(),
+            "4:13: unsupported node `Parallel`"
+        );
+    }
+
+    #[test]
+    fn test_from_btcpp_xml_parse_error() {
+        let errors = load("<root><BehaviorTree></root>").err().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, XmlErrorKind::Parse(_)));
+        assert_eq!((errors[0].line, errors[0].column), (1, 21));
+    }
+
+    #[test]
+    fn test_from_btcpp_xml_main_tree() {
+        let xml = r#"
+            <root>
+                <BehaviorTree ID="A"><Say/></BehaviorTree>
+                <BehaviorTree ID="B"><Say/></BehaviorTree>
+            </root>
+        "#;
+        let errors = load(xml).err().unwrap();
+        assert_eq!(
+            errors[0].kind,
+            XmlErrorKind::MissingAttribute {
+                tag: "root".to_owned(),
+                name: "main_tree_to_execute"
+            }
+        );
+    }
+}
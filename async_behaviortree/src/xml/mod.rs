@@ -0,0 +1,5 @@
+mod export;
+pub use export::*;
+
+mod import;
+pub use import::*;
@@ -0,0 +1,56 @@
+//! Prints the status changes streamed by a `MonitorServer`, run with
+//! `cargo run --features monitor --example monitor_client [-- <addr>]`
+
+use std::io::{self, BufRead, BufReader};
+use std::net::TcpStream;
+
+use behaviortree::{MONITOR_PROTOCOL_VERSION, MonitorMessage, StateSnapshot};
+
+fn print_tree(node: &StateSnapshot, depth: usize) {
+    println!(
+        "{:indent$}{} ({})",
+        "",
+        display_name(node),
+        node.id.0,
+        indent = depth * 2
+    );
+    for child in &node.children {
+        print_tree(child, depth + 1);
+    }
+}
+
+fn display_name(node: &StateSnapshot) -> &str {
+    node.label.as_deref().unwrap_or(&node.name)
+}
+
+fn main() -> io::Result<()> {
+    let addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:7878".into());
+    let stream = TcpStream::connect(&addr)?;
+    println!("Connected to {addr}");
+
+    let mut tree: Option<StateSnapshot> = None;
+    for line in BufReader::new(stream).lines() {
+        match serde_json::from_str(&line?)? {
+            MonitorMessage::Hello { version } if version != MONITOR_PROTOCOL_VERSION => {
+                return Err(io::Error::other(format!(
+                    "unsupported protocol version {version}"
+                )));
+            }
+            MonitorMessage::Hello { .. } => {}
+            MonitorMessage::Tree { tree: snapshot } => {
+                print_tree(&snapshot, 0);
+                tree = Some(snapshot);
+            }
+            MonitorMessage::Statuses { statuses } => {
+                let Some(tree) = &tree else { continue };
+                for (id, status) in statuses {
+                    let name = tree.find(id).map_or("?", display_name);
+                    println!("{name} ({}): {status:?}", id.0);
+                }
+            }
+        }
+    }
+    Ok(())
+}
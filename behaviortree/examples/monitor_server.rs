@@ -0,0 +1,66 @@
+//! Serves a looping tree to remote viewers, run with
+//! `cargo run --features monitor --example monitor_server [-- --websocket]`
+//!
+//! Watch it with `cargo run --features monitor --example monitor_client`.
+
+use std::io;
+use std::time::Duration;
+
+use behaviortree::{
+    ActionType, Behavior, BehaviorTree, ImmediateAction, MonitorServer, StateObserver,
+};
+
+const ADDR: &str = "127.0.0.1:7878";
+const DT: f64 = 0.1;
+
+/// Succeeds every other run
+#[derive(Default)]
+struct Toggle {
+    on: bool,
+}
+
+impl ImmediateAction<()> for Toggle {
+    fn run(&mut self, _delta: f64, _shared: &mut ()) -> bool {
+        self.on = !self.on;
+        self.on
+    }
+
+    fn reset(&mut self, _shared: &mut ()) {}
+
+    fn name(&self) -> &str {
+        "Toggle"
+    }
+}
+
+impl From<Toggle> for ActionType<()> {
+    fn from(toggle: Toggle) -> Self {
+        ActionType::Immediate(Box::new(toggle))
+    }
+}
+
+fn main() -> io::Result<()> {
+    let behavior = Behavior::Select(vec![
+        Behavior::Named(
+            "Blink".into(),
+            Box::new(Behavior::Sequence(vec![
+                Behavior::Action(Toggle::default()),
+                Behavior::Named("On".into(), Box::new(Behavior::Wait(1.0))),
+            ])),
+        ),
+        Behavior::Named("Off".into(), Box::new(Behavior::Wait(0.5))),
+    ]);
+    let mut tree = BehaviorTree::new(behavior, true, (), StateObserver::default());
+
+    let mut server = if std::env::args().any(|arg| arg == "--websocket") {
+        MonitorServer::bind_websocket(ADDR)?
+    } else {
+        MonitorServer::bind(ADDR)?
+    };
+    println!("Serving on {}", server.local_addr()?);
+
+    loop {
+        tree.tick(DT);
+        server.publish(&tree.state());
+        std::thread::sleep(Duration::from_secs_f64(DT));
+    }
+}
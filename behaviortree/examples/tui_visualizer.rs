@@ -0,0 +1,296 @@
+//! Live view of a looping patrol tree, run with `cargo run --example tui_visualizer`
+//!
+//! ↑/↓ selects a node, enter collapses it, PgUp/PgDn scrolls the change log,
+//! p pauses the simulation and q quits.
+
+use std::collections::{BTreeSet, VecDeque};
+use std::io;
+use std::time::Duration;
+
+use behaviortree::{
+    ActionType, Behavior, BehaviorTree, ImmediateAction, NodeId, StateObserver, StateSnapshot,
+    Status, SyncAction,
+};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+/// Milliseconds per tick, like every time of the tree
+const DT: f64 = 100.0;
+const LOG_LEN: usize = 500;
+
+struct Robot {
+    battery: f64,
+}
+
+enum Task {
+    MoveTo { duration: f64 },
+    IsBatteryLow,
+    Charge,
+}
+
+struct MoveState {
+    duration: f64,
+    elapsed: f64,
+}
+
+impl SyncAction<Robot> for MoveState {
+    fn tick(&mut self, delta: f64, shared: &mut Robot) -> Status {
+        self.elapsed += delta;
+        shared.battery -= 0.004 * delta;
+        if self.elapsed >= self.duration {
+            Status::Success
+        } else {
+            Status::Running
+        }
+    }
+
+    fn reset(&mut self, _shared: &mut Robot) {
+        self.elapsed = 0.0;
+    }
+
+    fn name(&self) -> &str {
+        "MoveTo"
+    }
+}
+
+struct IsBatteryLow;
+
+impl ImmediateAction<Robot> for IsBatteryLow {
+    fn run(&mut self, _delta: f64, shared: &mut Robot) -> bool {
+        shared.battery < 30.0
+    }
+
+    fn reset(&mut self, _shared: &mut Robot) {}
+
+    fn name(&self) -> &str {
+        "IsBatteryLow"
+    }
+}
+
+struct ChargeState;
+
+impl SyncAction<Robot> for ChargeState {
+    fn tick(&mut self, delta: f64, shared: &mut Robot) -> Status {
+        shared.battery = (shared.battery + 0.025 * delta).min(100.0);
+        if shared.battery < 100.0 {
+            Status::Running
+        } else {
+            Status::Success
+        }
+    }
+
+    fn reset(&mut self, _shared: &mut Robot) {}
+
+    fn name(&self) -> &str {
+        "Charge"
+    }
+}
+
+impl From<Task> for ActionType<Robot> {
+    fn from(task: Task) -> Self {
+        match task {
+            Task::MoveTo { duration } => ActionType::Sync(Box::new(MoveState {
+                duration,
+                elapsed: 0.0,
+            })),
+            Task::IsBatteryLow => ActionType::Immediate(Box::new(IsBatteryLow)),
+            Task::Charge => ActionType::Sync(Box::new(ChargeState)),
+        }
+    }
+}
+
+fn move_to(label: &str, duration: f64) -> Behavior<Task> {
+    Behavior::Named(
+        label.into(),
+        Box::new(Behavior::Action(Task::MoveTo { duration })),
+    )
+}
+
+fn patrol() -> Behavior<Task> {
+    Behavior::Select(vec![
+        Behavior::Named(
+            "Recharge".into(),
+            Box::new(Behavior::Sequence(vec![
+                Behavior::Action(Task::IsBatteryLow),
+                move_to("GoToDock", 1000.0),
+                Behavior::Action(Task::Charge),
+            ])),
+        ),
+        Behavior::Named(
+            "Patrol".into(),
+            Box::new(Behavior::Sequence(vec![
+                move_to("GoToKitchen", 2000.0),
+                Behavior::Wait(500.0),
+                move_to("GoToDoor", 1500.0),
+                Behavior::Invert(Box::new(Behavior::Action(Task::IsBatteryLow))),
+                move_to("GoToGarden", 2500.0),
+            ])),
+        ),
+    ])
+}
+
+/// Tree view and change log, fed with `State::snapshot` and `State::changes`
+#[derive(Default)]
+struct Viewer {
+    collapsed: BTreeSet<NodeId>,
+    selected: usize,
+    log: VecDeque<Line<'static>>,
+    /// Lines scrolled back from the newest change
+    log_scroll: usize,
+    paused: bool,
+}
+
+impl Viewer {
+    fn log(&mut self, time: f64, snapshot: &StateSnapshot, (id, status): (NodeId, Option<Status>)) {
+        let name = snapshot.find(id).map_or("?", display_name);
+        if self.log.len() == LOG_LEN {
+            self.log.pop_front();
+        }
+        self.log.push_back(Line::from(vec![
+            format!("{time:>8}ms ").dark_gray(),
+            format!("{id:>4} {name} ").into(),
+            status_span(status),
+        ]));
+    }
+
+    /// Visible nodes with their depth, children of collapsed nodes are hidden
+    fn rows<'a>(
+        &self,
+        node: &'a StateSnapshot,
+        depth: usize,
+        rows: &mut Vec<(usize, &'a StateSnapshot)>,
+    ) {
+        rows.push((depth, node));
+        if !self.collapsed.contains(&node.id) {
+            for child in &node.children {
+                self.rows(child, depth + 1, rows);
+            }
+        }
+    }
+
+    /// Returns `false` once the viewer should quit
+    fn handle(&mut self, key: KeyCode, snapshot: &StateSnapshot) -> bool {
+        let mut rows = Vec::new();
+        self.rows(snapshot, 0, &mut rows);
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('p') => self.paused = !self.paused,
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(rows.len() - 1),
+            KeyCode::PageUp => {
+                self.log_scroll = (self.log_scroll + 10).min(self.log.len().saturating_sub(1))
+            }
+            KeyCode::PageDown => self.log_scroll = self.log_scroll.saturating_sub(10),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some((_, node)) = rows.get(self.selected)
+                    && !node.children.is_empty()
+                    && !self.collapsed.remove(&node.id)
+                {
+                    self.collapsed.insert(node.id);
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn draw(&self, frame: &mut Frame, snapshot: &StateSnapshot, title: String) {
+        let [tree_area, log_area, help_area] = Layout::vertical([
+            Constraint::Percentage(60),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let mut rows = Vec::new();
+        self.rows(snapshot, 0, &mut rows);
+        let items = rows.iter().map(|(depth, node)| {
+            let marker = match (node.children.is_empty(), self.collapsed.contains(&node.id)) {
+                (true, _) => "  ",
+                (false, true) => "▸ ",
+                (false, false) => "▾ ",
+            };
+            ListItem::new(Line::from(vec![
+                "  ".repeat(*depth).into(),
+                marker.into(),
+                display_name(node).to_owned().bold(),
+                " ".into(),
+                status_span(node.status),
+            ]))
+        });
+        let tree = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::new().reversed());
+        let mut list_state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(tree, tree_area, &mut list_state);
+
+        // Newest changes at the bottom
+        let height = usize::from(log_area.height.saturating_sub(2));
+        let end = self.log.len() - self.log_scroll;
+        let lines: Vec<_> = self
+            .log
+            .range(end.saturating_sub(height)..end)
+            .cloned()
+            .collect();
+        let log = Paragraph::new(lines).block(Block::bordered().title(" Changes "));
+        frame.render_widget(log, log_area);
+
+        let paused = if self.paused { "PAUSED  " } else { "" };
+        let help = format!("{paused}↑/↓ select  enter collapse  PgUp/PgDn scroll  p pause  q quit");
+        frame.render_widget(Line::from(help).dark_gray(), help_area);
+    }
+}
+
+fn display_name(node: &StateSnapshot) -> &str {
+    node.label.as_deref().unwrap_or(&node.name)
+}
+
+fn status_span(status: Option<Status>) -> Span<'static> {
+    match status {
+        Some(Status::Success) => "Success".green(),
+        Some(Status::Failure) => "Failure".red(),
+        Some(Status::Running) => "Running".yellow(),
+        None => "-".dark_gray(),
+    }
+}
+
+fn run(terminal: &mut DefaultTerminal) -> io::Result<()> {
+    let robot = Robot { battery: 50.0 };
+    let mut tree = BehaviorTree::new(patrol(), true, robot, StateObserver::default());
+    let mut changes = tree.changes();
+    let mut viewer = Viewer::default();
+    let mut time = 0.0;
+
+    loop {
+        if !viewer.paused {
+            tree.tick(DT);
+            time += DT;
+        }
+        let snapshot = tree.state().snapshot();
+        for change in changes.drain() {
+            viewer.log(time, &snapshot, change);
+        }
+
+        let title = format!(" Patrol {time}ms ");
+        terminal.draw(|frame| viewer.draw(frame, &snapshot, title))?;
+
+        if event::poll(Duration::from_millis(DT as u64))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && !viewer.handle(key.code, &snapshot)
+        {
+            return Ok(());
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal);
+    ratatui::restore();
+    result
+}
@@ -1,3 +1,5 @@
+use alloc::{boxed::Box, string::String, vec::Vec};
+
 use crate::{ImmediateAction, Status, SyncAction};
 
 pub enum ActionType<S> {
@@ -27,7 +29,21 @@ impl<S> ActionType<S> {
         }
     }
 
-    pub fn name(&self) -> &'static str {
+    pub fn save(&self) -> Option<Vec<u8>> {
+        match self {
+            ActionType::Immediate(immediate_action) => immediate_action.save(),
+            ActionType::Sync(sync_action) => sync_action.save(),
+        }
+    }
+
+    pub fn load(&mut self, state: &[u8]) -> Result<(), String> {
+        match self {
+            ActionType::Immediate(immediate_action) => immediate_action.load(state),
+            ActionType::Sync(sync_action) => sync_action.load(state),
+        }
+    }
+
+    pub fn name(&self) -> &str {
         match self {
             ActionType::Immediate(immediate_action) => immediate_action.name(),
             ActionType::Sync(sync_action) => sync_action.name(),
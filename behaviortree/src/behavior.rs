@@ -1,15 +1,23 @@
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+
+use crate::{
+    BuildError,
+    path::{ROOT, child_path},
+};
+
 /// Describes a behavior.
 ///
 /// This is used for more complex event logic.
 /// Can also be used for game AI.
 #[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Behavior<A> {
     /// A high level description of an action.
     Action(A),
     /// Waits an amount of time before continuing.
     ///
     /// f64: Time in milliseconds
-    Wait(f64),
+    Wait(#[cfg_attr(feature = "schema", schemars(range(min = 0.0)))] f64),
 
     /// Converts `Success` into `Failure` and vice versa.
     Invert(Box<Behavior<A>>),
@@ -19,11 +27,170 @@ pub enum Behavior<A> {
     /// The sequence fails if a behavior fails.
     /// The sequence succeeds if all the behavior succeeds.
     /// Can be thought of as a short-circuited logical AND gate.
-    Sequence(Vec<Behavior<A>>),
+    Sequence(#[cfg_attr(feature = "schema", schemars(length(min = 1)))] Vec<Behavior<A>>),
     /// Runs behaviors one by one until a behavior succeeds.
     ///
     /// If a behavior fails it will try the next one.
     /// Fails if the last behavior fails.
     /// Can be thought of as a short-circuited logical OR gate.
-    Select(Vec<Behavior<A>>),
+    Select(#[cfg_attr(feature = "schema", schemars(length(min = 1)))] Vec<Behavior<A>>),
+
+    /// Gives the behavior a display label, shown in `State` and renders.
+    ///
+    /// i.e `Named("OpenDoor", Sequence(..))` to tell composites apart.
+    /// The outermost label wins when nested.
+    Named(String, Box<Behavior<A>>),
+}
+
+impl<A> Behavior<A> {
+    /// Checks the behavior for problems that would panic or stall at runtime.
+    ///
+    /// Returns every problem found, each with the path of the offending node.
+    pub fn validate(&self) -> Result<(), Vec<BuildError>> {
+        let mut errors = vec![];
+        self.validate_at(child_path(ROOT, None, self.kind_name()), &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn validate_at(&self, path: String, errors: &mut Vec<BuildError>) {
+        match self {
+            Behavior::Action(_) => {}
+            Behavior::Wait(target) => {
+                if !is_valid_wait(*target) {
+                    errors.push(BuildError::InvalidWait {
+                        path,
+                        target: *target,
+                    });
+                }
+            }
+            Behavior::Invert(child) => {
+                child.validate_at(child_path(&path, None, child.kind_name()), errors);
+            }
+            Behavior::Named(_, behavior) => behavior.validate_at(path, errors),
+            Behavior::Sequence(children) | Behavior::Select(children) => {
+                for (index, child) in children.iter().enumerate() {
+                    child.validate_at(child_path(&path, Some(index), child.kind_name()), errors);
+                }
+                if children.is_empty() {
+                    errors.push(BuildError::EmptyComposite { path });
+                }
+            }
+        }
+    }
+
+    /// Name of the behavior kind, used to build node paths
+    pub(crate) fn kind_name(&self) -> &'static str {
+        match self {
+            Behavior::Action(_) => "Action",
+            Behavior::Wait(_) => "Wait",
+            Behavior::Invert(_) => "Invert",
+            Behavior::Sequence(_) => "Sequence",
+            Behavior::Select(_) => "Select",
+            Behavior::Named(_, behavior) => behavior.kind_name(),
+        }
+    }
+}
+
+#[cfg(feature = "schema")]
+impl<A: schemars::JsonSchema> Behavior<A> {
+    /// JSON Schema of a serialized tree, composed with the schema of `A`
+    pub fn json_schema() -> schemars::Schema {
+        schemars::schema_for!(Self)
+    }
+}
+
+/// Rejects `NaN` and negative times
+pub(crate) fn is_valid_wait(target: f64) -> bool {
+    target >= 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(()),
+            Behavior::Wait(0.0),
+            Behavior::Invert(Box::new(Behavior::Wait(f64::INFINITY))),
+        ]);
+        assert_eq!(behavior.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(()),
+            Behavior::Invert(Box::new(Behavior::Select(vec![]))),
+            Behavior::Select(vec![Behavior::Wait(-1.0), Behavior::Wait(f64::NAN)]),
+        ]);
+
+        let errors = behavior.validate().unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(
+            errors[0],
+            BuildError::EmptyComposite {
+                path: "root/Sequence[1]/Invert/Select".into()
+            }
+        );
+        assert_eq!(
+            errors[1],
+            BuildError::InvalidWait {
+                path: "root/Sequence[2]/Select[0]/Wait".into(),
+                target: -1.0
+            }
+        );
+        assert!(matches!(
+            &errors[2],
+            BuildError::InvalidWait { path, target }
+                if path == "root/Sequence[2]/Select[1]/Wait" && target.is_nan()
+        ));
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_json_schema() {
+        #[derive(schemars::JsonSchema)]
+        #[allow(dead_code)]
+        enum Action {
+            Say { message: String },
+        }
+
+        let schema = Behavior::<Action>::json_schema().to_value();
+        let variants = schema["oneOf"].as_array().unwrap();
+        assert_eq!(variants.len(), 6);
+
+        assert_eq!(
+            variants[0]["properties"]["Action"]["$ref"],
+            "#/$defs/Action"
+        );
+        assert!(schema["$defs"]["Action"].is_object());
+
+        let wait = &variants[1];
+        assert!(
+            wait["description"]
+                .as_str()
+                .unwrap()
+                .contains("milliseconds")
+        );
+        assert_eq!(wait["properties"]["Wait"]["minimum"], 0.0);
+        assert_eq!(variants[3]["properties"]["Sequence"]["minItems"], 1);
+        assert_eq!(variants[5]["properties"]["Named"]["type"], "array");
+    }
+
+    #[test]
+    fn test_validate_root() {
+        let behavior = Behavior::<()>::Select(vec![]);
+        assert_eq!(
+            behavior.validate(),
+            Err(vec![BuildError::EmptyComposite {
+                path: "root/Select".into()
+            }])
+        );
+    }
 }
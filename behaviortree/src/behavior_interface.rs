@@ -1,3 +1,5 @@
+use alloc::{string::String, vec::Vec};
+
 use crate::Status;
 
 pub trait ImmediateAction<S> {
@@ -11,8 +13,20 @@ pub trait ImmediateAction<S> {
     /// Resets the current action to its initial/newly created state
     fn reset(&mut self, shared: &mut S);
 
-    /// Identify your action
-    fn name(&self) -> &'static str;
+    /// Identify your action, may borrow an owned name from the action
+    fn name(&self) -> &str;
+
+    /// Saves the execution state of the action, see `BehaviorTree::snapshot`
+    ///
+    /// Returns `None` by default, for actions without state worth saving.
+    fn save(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Restores the state returned by `save`, see `BehaviorTree::restore`
+    fn load(&mut self, _state: &[u8]) -> Result<(), String> {
+        Err(String::from("loading state is not supported"))
+    }
 }
 
 /// Modelled after the `std::future::Future` trait
@@ -30,8 +44,20 @@ pub trait SyncAction<S> {
     /// Resets the current action to its initial/newly created state
     fn reset(&mut self, shared: &mut S);
 
-    /// Identify your action
-    fn name(&self) -> &'static str;
+    /// Identify your action, may borrow an owned name from the action
+    fn name(&self) -> &str;
+
+    /// Saves the execution state of the action, see `BehaviorTree::snapshot`
+    ///
+    /// Returns `None` by default, for actions without state worth saving.
+    fn save(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Restores the state returned by `save`, see `BehaviorTree::restore`
+    fn load(&mut self, _state: &[u8]) -> Result<(), String> {
+        Err(String::from("loading state is not supported"))
+    }
 }
 
 // TODO, Shift this also
@@ -62,7 +88,7 @@ pub mod test_behavior_interface {
     }
 
     struct GenericTestSyncAction {
-        name: &'static str,
+        name: String,
         status: bool,
         times: usize,
         elapsed: usize,
@@ -71,7 +97,7 @@ pub mod test_behavior_interface {
     impl GenericTestSyncAction {
         fn new(name: String, status: bool, times: usize) -> Self {
             Self {
-                name: Box::new(name).leak(),
+                name,
                 status,
                 times,
                 elapsed: 0,
@@ -97,8 +123,18 @@ pub mod test_behavior_interface {
             self.elapsed = 0;
         }
 
-        fn name(&self) -> &'static str {
-            self.name
+        fn save(&self) -> Option<Vec<u8>> {
+            Some(self.elapsed.to_le_bytes().to_vec())
+        }
+
+        fn load(&mut self, state: &[u8]) -> Result<(), String> {
+            let elapsed = state.try_into().map_err(|_| "expected 8 bytes")?;
+            self.elapsed = usize::from_le_bytes(elapsed);
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            &self.name
         }
     }
 
@@ -1,4 +1,8 @@
-use crate::{Status, SyncAction, child::Child};
+use alloc::boxed::Box;
+
+use crate::{
+    NodeSnapshot, NodeState, SnapshotError, Status, SyncAction, child::Child, path::child_path,
+};
 
 pub struct InvertState<S> {
     child: Child<S>,
@@ -14,6 +18,36 @@ impl<S> InvertState<S> {
     }
 }
 
+impl<S> InvertState<S> {
+    pub fn children(&self) -> &[Child<S>] {
+        core::slice::from_ref(&self.child)
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn children_mut(&mut self) -> &mut [Child<S>] {
+        core::slice::from_mut(&mut self.child)
+    }
+
+    pub fn snapshot(&self) -> NodeState {
+        NodeState::Invert {
+            completed: self.completed,
+            child: Box::new(self.child.snapshot()),
+        }
+    }
+
+    pub fn restore(
+        &mut self,
+        completed: bool,
+        child: NodeSnapshot,
+        path: &str,
+    ) -> Result<(), SnapshotError> {
+        let child_path = child_path(path, None, self.child.name());
+        self.child.restore(child, &child_path)?;
+        self.completed = completed;
+        Ok(())
+    }
+}
+
 impl<S> SyncAction<S> for InvertState<S> {
     fn tick(&mut self, delta: f64, shared: &mut S) -> Status {
         match self.completed {
@@ -1,4 +1,8 @@
-use crate::{Status, SyncAction, child::Child};
+use alloc::{string::String, vec::Vec};
+
+use crate::{
+    NodeSnapshot, NodeState, SnapshotError, Status, SyncAction, child::Child, path::child_path,
+};
 
 pub struct SequenceState<S> {
     children: Vec<Child<S>>,
@@ -17,6 +21,53 @@ impl<S> SequenceState<S> {
     }
 }
 
+impl<S> SequenceState<S> {
+    pub fn children(&self) -> &[Child<S>] {
+        &self.children
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn children_mut(&mut self) -> &mut [Child<S>] {
+        &mut self.children
+    }
+
+    pub fn snapshot(&self) -> NodeState {
+        NodeState::Sequence {
+            index: self.index,
+            completed: self.completed,
+            children: self.children.iter().map(Child::snapshot).collect(),
+        }
+    }
+
+    pub fn restore(
+        &mut self,
+        index: usize,
+        completed: bool,
+        children: Vec<NodeSnapshot>,
+        path: &str,
+    ) -> Result<(), SnapshotError> {
+        let len = self.children.len();
+        if children.len() != len {
+            return Err(SnapshotError::StructureMismatch {
+                path: String::from(path),
+            });
+        }
+        // The index only reaches `len` once the last child has completed
+        if index > len || (index == len && !completed) {
+            return Err(SnapshotError::InvalidState {
+                path: String::from(path),
+            });
+        }
+
+        for (index, (child, snapshot)) in self.children.iter_mut().zip(children).enumerate() {
+            child.restore(snapshot, &child_path(path, Some(index), child.name()))?;
+        }
+        self.index = index;
+        self.completed = completed;
+        Ok(())
+    }
+}
+
 impl<S> SyncAction<S> for SequenceState<S> {
     fn tick(&mut self, dt: f64, shared: &mut S) -> Status {
         match self.completed {
@@ -1,19 +1,34 @@
-use crate::{Status, SyncAction};
+use crate::{NodeState, Status, SyncAction};
 
 pub struct WaitState {
     target: f64,
     elapsed: f64,
+    completed: bool,
+}
+
+impl WaitState {
+    pub fn snapshot(&self) -> NodeState {
+        NodeState::Wait {
+            elapsed: self.elapsed,
+            completed: self.completed,
+        }
+    }
+
+    pub fn restore(&mut self, elapsed: f64, completed: bool) {
+        self.elapsed = elapsed;
+        self.completed = completed;
+    }
 }
 
 impl<S> SyncAction<S> for WaitState {
     fn tick(&mut self, dt: f64, _shared: &mut S) -> Status {
-        match self.elapsed >= self.target {
-            true => unreachable!(),
-            false => {}
+        if self.completed {
+            unreachable!();
         }
 
         self.elapsed += dt;
         if self.elapsed >= self.target {
+            self.completed = true;
             Status::Success
         } else {
             Status::Running
@@ -22,6 +37,7 @@ impl<S> SyncAction<S> for WaitState {
 
     fn reset(&mut self, _shared: &mut S) {
         self.elapsed = 0.0;
+        self.completed = false;
     }
 
     fn name(&self) -> &'static str {
@@ -34,6 +50,7 @@ impl WaitState {
         Self {
             target,
             elapsed: 0.0,
+            completed: false,
         }
     }
 }
@@ -70,6 +87,17 @@ mod tests {
         assert_eq!(status, Status::Success);
     }
 
+    #[test]
+    fn test_wait_zero() {
+        let mut shared = TestShared;
+
+        let mut wait = WaitState::new(0.0);
+        let wait_ref_mut: &mut dyn SyncAction<TestShared> = &mut wait;
+
+        let status = wait_ref_mut.tick(0.0, &mut shared);
+        assert_eq!(status, Status::Success);
+    }
+
     #[test]
     fn test_wait_from_behavior() {
         let mut shared = TestShared::default();
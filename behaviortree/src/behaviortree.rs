@@ -1,30 +1,76 @@
-use crate::{Behavior, State, Status, action_type::ActionType, child::Child};
+use alloc::{rc::Rc, vec::Vec};
+use core::cell::{Ref, RefCell};
 
-pub struct BehaviorTree<S> {
+#[cfg(feature = "metrics")]
+use crate::Metrics;
+use crate::{
+    Behavior, BuildError, NodeId, NodeSnapshot, SnapshotError, Status, TreeObserver,
+    action_type::ActionType,
+    child::{Child, observer_ref},
+};
+#[cfg(feature = "observe")]
+use crate::{State, StateChanges, StateObserver};
+
+pub struct BehaviorTree<S, O = ()> {
     child: Child<S>,
     should_loop: bool,
-    state: State,
     shared: S,
-    statuses: Vec<tokio::sync::watch::Sender<Option<Status>>>,
+    observer: Rc<RefCell<O>>,
+    /// Id of the next node, kept across `replace_behavior`
+    next_id: usize,
 }
 
-impl<S> BehaviorTree<S> {
-    pub fn new<A>(behavior: Behavior<A>, should_loop: bool, shared: S) -> Self
+impl<S, O> BehaviorTree<S, O> {
+    pub fn new<A>(behavior: Behavior<A>, should_loop: bool, shared: S, observer: O) -> Self
     where
         A: Into<ActionType<S>>,
         S: 'static,
+        O: TreeObserver + 'static,
     {
-        let mut statuses = vec![];
-        let (child, state) = Child::from_behavior_with_state_and_status(behavior, &mut statuses);
+        let observer = Rc::new(RefCell::new(observer));
+        let mut next_id = 0;
+        let child =
+            Child::from_behavior_with_observer(behavior, &observer_ref(&observer), &mut next_id);
         Self {
             child,
             should_loop,
-            state,
             shared,
-            statuses,
+            observer,
+            next_id,
         }
     }
 
+    /// Same as [`BehaviorTree::new`], but rejects behaviors that fail [`Behavior::validate`]
+    pub fn try_new<A>(
+        behavior: Behavior<A>,
+        should_loop: bool,
+        shared: S,
+        observer: O,
+    ) -> Result<Self, Vec<BuildError>>
+    where
+        A: Into<ActionType<S>>,
+        S: 'static,
+        O: TreeObserver + 'static,
+    {
+        behavior.validate()?;
+        Ok(Self::new(behavior, should_loop, shared, observer))
+    }
+
+    /// Resets the current tree and replaces it with `behavior`, keeping the shared state
+    ///
+    /// The new nodes are reported to the observer with fresh ids,
+    /// before the nodes of the current tree are removed.
+    pub fn replace_behavior<A>(&mut self, behavior: Behavior<A>)
+    where
+        A: Into<ActionType<S>>,
+        S: 'static,
+        O: TreeObserver + 'static,
+    {
+        self.child.reset(&mut self.shared);
+        let observer = observer_ref(&self.observer);
+        self.child = Child::from_behavior_with_observer(behavior, &observer, &mut self.next_id);
+    }
+
     pub fn tick(&mut self, dt: f64) -> Status {
         if let Some(status) = self.child.status() {
             let completed = status != Status::Running;
@@ -37,30 +83,113 @@ impl<S> BehaviorTree<S> {
             }
         }
 
-        self.child.tick(dt, &mut self.shared)
+        self.child.tick_root(dt, &mut self.shared)
     }
 
-    pub fn state(&self) -> State {
-        self.state.clone()
+    pub fn observer(&self) -> Ref<'_, O> {
+        self.observer.borrow()
+    }
+
+    pub fn shared(&self) -> &S {
+        &self.shared
+    }
+
+    pub fn shared_mut(&mut self) -> &mut S {
+        &mut self.shared
     }
 
     pub fn reset(&mut self) {
-        self.statuses.iter_mut().for_each(|status| {
-            status.send_replace(None);
-        });
-        self.child.reset(&mut self.shared);
+        self.child.reset_root(&mut self.shared);
     }
 
     pub fn status(&self) -> Option<Status> {
         self.child.status()
     }
+
+    /// Id of the root node, ids of the other nodes are reported by the observer and `State`
+    pub fn root_id(&self) -> NodeId {
+        self.child.id()
+    }
+
+    /// Status of the node `id`, `None` if it is idle or not part of this tree
+    pub fn node_status(&self, id: NodeId) -> Option<Status> {
+        self.child.find(id)?.status()
+    }
+
+    /// Path of the node `id`, i.e `root/Sequence[1]/Invert/Action`
+    pub fn node_path(&self, id: NodeId) -> Option<&str> {
+        Some(self.child.find(id)?.path())
+    }
+
+    /// Calls `f` with the path and status of every node of the current tree
+    #[cfg(feature = "testing")]
+    pub(crate) fn for_each_node(&self, mut f: impl FnMut(&str, Option<Status>)) {
+        self.child
+            .visit(&mut |child| f(child.path(), child.status()));
+    }
+
+    /// Label given to the node `id` by `Behavior::Named`
+    pub fn node_label(&self, id: NodeId) -> Option<&str> {
+        self.child.find(id)?.label()
+    }
+
+    /// Counters of every node keyed by path, kept across resets until [`BehaviorTree::reset_metrics`]
+    ///
+    /// Replaced behaviors start over from zero.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> Metrics {
+        let mut metrics = Metrics::new();
+        self.child.metrics(&mut metrics);
+        metrics
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn reset_metrics(&mut self) {
+        self.child.reset_metrics();
+    }
+
+    /// Saves the execution state of every node, to be restored with [`BehaviorTree::restore`]
+    ///
+    /// Actions add their own state through `SyncAction::save`/`ImmediateAction::save`.
+    pub fn snapshot(&self) -> NodeSnapshot {
+        self.child.snapshot()
+    }
+
+    /// Restores a snapshot taken from a tree built from the same behavior
+    ///
+    /// The tree is reset when the snapshot does not match its structure.
+    pub fn restore(&mut self, snapshot: NodeSnapshot) -> Result<(), SnapshotError> {
+        let restored = self.child.restore_root(snapshot);
+        if restored.is_err() {
+            self.reset();
+        }
+        restored
+    }
+}
+
+#[cfg(feature = "observe")]
+impl<S> BehaviorTree<S, StateObserver> {
+    pub fn state(&self) -> State {
+        self.observer.borrow().state()
+    }
+
+    /// Status changes of every node of the current tree, see [`StateObserver::changes`]
+    pub fn changes(&self) -> StateChanges {
+        self.observer.borrow_mut().changes()
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    #[cfg(feature = "metrics")]
+    use crate::NodeMetrics;
+    use crate::NodeState;
+    #[cfg(feature = "observe")]
+    use crate::StateSnapshot;
     use crate::test_behavior_interface::{TestAction, TestShared};
+    use crate::test_observer::TestObserver;
 
     #[test]
     fn behavior_tree_with_reset() {
@@ -68,11 +197,7 @@ mod tests {
             Behavior::Action(TestAction::Success),
             Behavior::Action(TestAction::Success),
         ]);
-        let mut tree = BehaviorTree::new(behavior, false, TestShared);
-        let _state = tree.state();
-
-        // For unit tests
-        let _state = tree.state();
+        let mut tree = BehaviorTree::new(behavior, false, TestShared, ());
         assert_eq!(tree.status(), None);
 
         let status = tree.tick(0.1);
@@ -100,7 +225,7 @@ mod tests {
             Behavior::Action(TestAction::Success),
             Behavior::Action(TestAction::Success),
         ]);
-        let mut tree = BehaviorTree::new(behavior, true, TestShared);
+        let mut tree = BehaviorTree::new(behavior, true, TestShared, ());
 
         let status = tree.tick(0.1);
         assert_eq!(status, Status::Running);
@@ -116,4 +241,425 @@ mod tests {
         let status = tree.tick(0.1);
         assert_eq!(status, Status::Success);
     }
+
+    #[test]
+    fn behavior_tree_try_new() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Wait(0.0),
+        ]);
+        let mut tree = BehaviorTree::try_new(behavior, false, TestShared, ()).unwrap();
+        assert_eq!(tree.tick(0.0), Status::Running);
+        assert_eq!(tree.tick(0.0), Status::Success);
+
+        let behavior = Behavior::Select(vec![Behavior::Sequence(vec![]), Behavior::Wait(f64::NAN)]);
+        let errors = BehaviorTree::<_, ()>::try_new::<TestAction>(behavior, false, TestShared, ())
+            .err()
+            .unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0],
+            BuildError::EmptyComposite {
+                path: "root/Select[0]/Sequence".into()
+            }
+        );
+    }
+
+    #[test]
+    fn behavior_tree_replace_behavior() {
+        let behavior = Behavior::Action(TestAction::SuccessAfter { times: 2 });
+        let mut tree = BehaviorTree::new(behavior, false, TestShared, TestObserver::default());
+        assert_eq!(tree.tick(0.1), Status::Running);
+
+        let behavior = Behavior::Invert(Behavior::Action(TestAction::Success).into());
+        tree.replace_behavior(behavior);
+        assert_eq!(tree.status(), None);
+        assert_eq!(tree.tick(0.1), Status::Failure);
+
+        let observer = tree.observer();
+        assert_eq!(observer.names, ["SuccessAfter2", "Success", "Invert"]);
+        use Status::*;
+        assert_eq!(
+            observer.changes,
+            [
+                (0, None, Some(Running)),
+                (0, Some(Running), None),
+                (1, None, Some(Success)),
+                (2, None, Some(Failure)),
+            ]
+        );
+    }
+
+    #[cfg(feature = "observe")]
+    #[test]
+    fn behavior_tree_replace_behavior_state() {
+        let behavior = Behavior::Action(TestAction::SuccessAfter { times: 2 });
+        let mut tree = BehaviorTree::new(behavior, false, TestShared, StateObserver::default());
+        let old_state = tree.state();
+        tree.tick(0.1);
+
+        tree.replace_behavior(Behavior::Action(TestAction::Failure));
+        let State::NoChild(_, old_status) = &old_state else {
+            unreachable!()
+        };
+        assert!(old_status.has_changed().is_err());
+
+        let state = tree.state();
+        let State::NoChild(name, status) = &state else {
+            unreachable!()
+        };
+        assert_eq!(&*name.name, "Failure");
+        assert_eq!(tree.tick(0.1), Status::Failure);
+        assert_eq!(*status.borrow(), Some(Status::Failure));
+    }
+
+    #[test]
+    fn behavior_tree_snapshot() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Wait(2.0),
+            Behavior::Invert(Behavior::Action(TestAction::FailureAfter { times: 2 }).into()),
+        ]);
+        let mut tree = BehaviorTree::new(behavior.clone(), false, TestShared, ());
+        tree.tick(0.5);
+        tree.tick(1.0);
+        tree.tick(1.0);
+        tree.tick(1.0);
+
+        let snapshot = tree.snapshot();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let snapshot: NodeSnapshot = serde_json::from_str(&json).unwrap();
+        assert!(snapshot == tree.snapshot());
+
+        let mut restored = BehaviorTree::new(behavior, false, TestShared, TestObserver::default());
+        restored.restore(snapshot).unwrap();
+        assert_eq!(restored.status(), Some(Status::Running));
+        assert_eq!(restored.observer().changes.len(), 5);
+
+        for _ in 0..2 {
+            assert_eq!(restored.tick(1.0), tree.tick(1.0));
+        }
+        assert_eq!(restored.status(), Some(Status::Success));
+    }
+
+    #[test]
+    fn behavior_tree_restore_mismatch() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Wait(2.0),
+        ]);
+        let mut tree = BehaviorTree::new(behavior, false, TestShared, ());
+        tree.tick(1.0);
+        let snapshot = tree.snapshot();
+
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Invert(Behavior::Wait(2.0).into()),
+        ]);
+        let mut other = BehaviorTree::new(behavior, false, TestShared, ());
+        assert_eq!(
+            other.restore(snapshot.clone()),
+            Err(SnapshotError::NameMismatch {
+                path: "root/Sequence[1]/Invert".into(),
+                expected: "Invert".into(),
+                found: "Wait".into()
+            })
+        );
+        assert_eq!(other.status(), None);
+
+        let mut invalid = snapshot;
+        let NodeState::Sequence { index, .. } = &mut invalid.state else {
+            unreachable!()
+        };
+        *index = 2;
+        assert_eq!(
+            tree.restore(invalid),
+            Err(SnapshotError::InvalidState {
+                path: "root/Sequence".into()
+            })
+        );
+    }
+
+    #[test]
+    fn behavior_tree_restore_inconsistent_status() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Wait(2.0),
+        ]);
+        let mut tree = BehaviorTree::new(behavior, false, TestShared, ());
+        tree.tick(1.0);
+        tree.tick(1.0);
+        let snapshot = tree.snapshot();
+
+        // A completed sequence that is still running
+        let mut running = snapshot.clone();
+        let NodeState::Sequence { completed, .. } = &mut running.state else {
+            unreachable!()
+        };
+        *completed = true;
+        assert_eq!(
+            tree.restore(running),
+            Err(SnapshotError::InconsistentStatus {
+                path: "root/Sequence".into(),
+                status: Some(Status::Running),
+                completed: true
+            })
+        );
+        assert_eq!(tree.status(), None);
+
+        // A wait that succeeded without completing
+        let mut succeeded = snapshot;
+        let NodeState::Sequence { children, .. } = &mut succeeded.state else {
+            unreachable!()
+        };
+        children[1].status = Some(Status::Success);
+        assert_eq!(
+            tree.restore(succeeded),
+            Err(SnapshotError::InconsistentStatus {
+                path: "root/Sequence[1]/Wait".into(),
+                status: Some(Status::Success),
+                completed: false
+            })
+        );
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn behavior_tree_metrics() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Invert(Behavior::Wait(1.5).into()),
+        ]);
+        let mut tree = BehaviorTree::new(behavior, true, TestShared, ());
+        for _ in 0..3 {
+            tree.tick(1.0);
+        }
+        assert_eq!(tree.status(), Some(Status::Failure));
+
+        let metrics = tree.metrics();
+        let counters = |path: &str| {
+            let metrics = metrics[path];
+            (
+                metrics.ticks,
+                metrics.successes,
+                metrics.failures,
+                metrics.running_time,
+            )
+        };
+        assert_eq!(metrics.len(), 4);
+        assert_eq!(counters("root/Sequence"), (3, 0, 1, 2.0));
+        assert_eq!(counters("root/Sequence[0]/Success"), (1, 1, 0, 0.0));
+        assert_eq!(counters("root/Sequence[1]/Invert"), (2, 0, 1, 1.0));
+        assert_eq!(counters("root/Sequence[1]/Invert/Wait"), (2, 1, 0, 1.0));
+        #[cfg(feature = "std")]
+        assert!(metrics["root/Sequence"].tick_time >= metrics["root/Sequence[1]/Invert"].tick_time);
+
+        // Counters are kept across resets
+        tree.tick(0.5);
+        assert_eq!(tree.metrics()["root/Sequence"].ticks, 4);
+
+        tree.reset_metrics();
+        assert!(
+            tree.metrics()
+                .values()
+                .all(|metrics| *metrics == NodeMetrics::default())
+        );
+    }
+
+    #[cfg(feature = "observe")]
+    #[test]
+    fn behavior_tree_with_state() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Invert(Behavior::Action(TestAction::Success).into()),
+        ]);
+        let mut tree = BehaviorTree::new(behavior, false, TestShared, StateObserver::default());
+        let state = tree.state();
+
+        let State::MultipleChildren(name, status, children) = &state else {
+            unreachable!()
+        };
+        assert_eq!(&*name.name, "Sequence");
+        assert_eq!(*status.borrow(), None);
+
+        let status = tree.tick(0.1);
+        assert_eq!(status, Status::Running);
+        let State::NoChild(name, status) = &children[0] else {
+            unreachable!()
+        };
+        assert_eq!(&*name.name, "Success");
+        assert_eq!(*status.borrow(), Some(Status::Success));
+
+        let status = tree.tick(0.1);
+        assert_eq!(status, Status::Failure);
+        let State::SingleChild(name, status, child) = &children[1] else {
+            unreachable!()
+        };
+        assert_eq!(&*name.name, "Invert");
+        assert_eq!(*status.borrow(), Some(Status::Failure));
+        let State::NoChild(_, status) = &**child else {
+            unreachable!()
+        };
+        assert_eq!(*status.borrow(), Some(Status::Success));
+
+        tree.reset();
+        let State::MultipleChildren(_, status, _) = &state else {
+            unreachable!()
+        };
+        assert_eq!(*status.borrow(), None);
+    }
+
+    #[cfg(feature = "observe")]
+    #[test]
+    fn behavior_tree_state_changes() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Invert(Box::new(Behavior::Action(TestAction::Failure))),
+        ]);
+        let mut tree = BehaviorTree::new(behavior, true, TestShared, StateObserver::default());
+        let mut changes = tree.changes();
+        assert_eq!(changes.drain().next(), None);
+
+        tree.tick(0.1);
+        assert_eq!(
+            changes.drain().collect::<Vec<_>>(),
+            vec![
+                (NodeId(0), Some(Status::Success)),
+                (NodeId(3), Some(Status::Running))
+            ]
+        );
+
+        tree.tick(0.1);
+        assert_eq!(
+            changes.drain().collect::<Vec<_>>(),
+            vec![
+                (NodeId(1), Some(Status::Failure)),
+                (NodeId(2), Some(Status::Success)),
+                (NodeId(3), Some(Status::Success))
+            ]
+        );
+        assert_eq!(changes.drain().next(), None);
+
+        // The loop resets every node within the next tick, `NodeId(0)` succeeds again
+        tree.tick(0.1);
+        assert_eq!(
+            changes.drain().collect::<Vec<_>>(),
+            vec![
+                (NodeId(0), None),
+                (NodeId(1), None),
+                (NodeId(2), None),
+                (NodeId(3), None),
+                (NodeId(0), Some(Status::Success)),
+                (NodeId(3), Some(Status::Running))
+            ]
+        );
+    }
+
+    #[cfg(feature = "observe")]
+    #[test]
+    fn behavior_tree_state_changes_replace_behavior() {
+        let behavior = Behavior::Action(TestAction::Success);
+        let mut tree = BehaviorTree::new(behavior, false, TestShared, StateObserver::default());
+        let mut changes = tree.changes();
+        tree.tick(0.1);
+
+        // The old node is reset before being removed, the new nodes are followed
+        let behavior = Behavior::Invert(Box::new(Behavior::Action(TestAction::Success)));
+        tree.replace_behavior(behavior);
+        tree.tick(0.1);
+        assert_eq!(
+            changes.drain().collect::<Vec<_>>(),
+            vec![
+                (NodeId(0), Some(Status::Success)),
+                (NodeId(0), None),
+                (NodeId(1), Some(Status::Success)),
+                (NodeId(2), Some(Status::Failure))
+            ]
+        );
+    }
+
+    #[cfg(feature = "observe")]
+    #[test]
+    fn behavior_tree_state_snapshot() {
+        let behavior = Behavior::Named(
+            "Greet".into(),
+            Box::new(Behavior::Sequence(vec![
+                Behavior::Action(TestAction::Success),
+                Behavior::Action(TestAction::Success),
+            ])),
+        );
+        let mut tree = BehaviorTree::new(behavior, false, TestShared, StateObserver::default());
+        let old = tree.state().snapshot();
+        assert_eq!(old.id, tree.root_id());
+        assert_eq!(old.label.as_deref(), Some("Greet"));
+        assert_eq!(old.children[1].path, "root/Sequence[1]/Success");
+
+        tree.tick(0.1);
+        let new = tree.state().snapshot();
+        assert_eq!(new.status, Some(Status::Running));
+        assert_eq!(new.children[0].status, Some(Status::Success));
+
+        let mut remote: StateSnapshot =
+            serde_json::from_str(&serde_json::to_string(&old).unwrap()).unwrap();
+        remote.apply(&old.diff(&new));
+        assert_eq!(remote, new);
+    }
+
+    #[cfg(feature = "observe")]
+    #[test]
+    fn behavior_tree_node_ids() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Action(TestAction::Success),
+        ]);
+        let mut tree = BehaviorTree::new(behavior, false, TestShared, StateObserver::default());
+        let state = tree.state();
+        assert_eq!(state.node().id, tree.root_id());
+        assert_eq!(&*state.node().path, "root/Sequence");
+
+        let [first, second] = state.children() else {
+            unreachable!()
+        };
+        let (first, second) = (first.node(), second.node());
+        assert_eq!((&*first.name, &*second.name), ("Success", "Success"));
+        assert_ne!(first.id, second.id);
+        assert_eq!(&*first.path, "root/Sequence[0]/Success");
+        assert_eq!(&*second.path, "root/Sequence[1]/Success");
+        assert_eq!(tree.node_path(second.id), Some("root/Sequence[1]/Success"));
+        assert_eq!(state.find(second.id).unwrap().node(), second);
+
+        tree.tick(0.1);
+        assert_eq!(tree.node_status(first.id), Some(Status::Success));
+        assert_eq!(tree.node_status(second.id), None);
+        assert_eq!(tree.observer().node_status(first.id), Some(Status::Success));
+        assert_eq!(tree.node_status(NodeId(3)), None);
+    }
+
+    #[cfg(feature = "observe")]
+    #[test]
+    fn behavior_tree_named() {
+        let behavior = Behavior::Named(
+            "Outer".into(),
+            Box::new(Behavior::Named(
+                "Inner".into(),
+                Box::new(Behavior::Sequence(vec![Behavior::Named(
+                    "Greet".into(),
+                    Box::new(Behavior::Action(TestAction::Success)),
+                )])),
+            )),
+        );
+        let tree = BehaviorTree::new(behavior, false, TestShared, StateObserver::default());
+        let state = tree.state();
+        assert_eq!(&*state.node().name, "Sequence");
+        assert_eq!(state.node().label.as_deref(), Some("Outer"));
+        assert_eq!(&*state.node().path, "root/Sequence");
+
+        let [child] = state.children() else {
+            unreachable!()
+        };
+        assert_eq!(&*child.node().name, "Success");
+        assert_eq!(child.node().label.as_deref(), Some("Greet"));
+        assert_eq!(tree.node_label(child.node().id), Some("Greet"));
+        assert_eq!(&*child.node().path, "root/Sequence[0]/Success");
+    }
 }
@@ -0,0 +1,51 @@
+use alloc::string::String;
+
+/// A problem found while building a behavior tree.
+///
+/// Nodes are identified by their runtime path, i.e `root/Sequence[1]/Invert/MoveTo`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    /// The builder did not describe exactly one root behavior.
+    RootChildren { found: usize },
+    /// A `Sequence` or `Select` without children.
+    EmptyComposite { path: String },
+    /// A decorator without exactly one child.
+    DecoratorChildren { path: String, found: usize },
+    /// A `Wait` with a negative or `NaN` time.
+    InvalidWait { path: String, target: f64 },
+    /// An action name without a factory in the `ActionRegistry`.
+    UnknownAction { path: String, name: String },
+    /// An action whose factory rejected its parameters.
+    InvalidParams {
+        path: String,
+        name: String,
+        reason: String,
+    },
+}
+
+impl core::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BuildError::RootChildren { found } => {
+                write!(f, "expected exactly one root behavior, found {found}")
+            }
+            BuildError::EmptyComposite { path } => {
+                write!(f, "`{path}` must have at least one child")
+            }
+            BuildError::DecoratorChildren { path, found } => {
+                write!(f, "`{path}` must have exactly one child, found {found}")
+            }
+            BuildError::InvalidWait { path, target } => {
+                write!(f, "`{path}` must wait a non-negative time, found {target}")
+            }
+            BuildError::UnknownAction { path, name } => {
+                write!(f, "`{path}` refers to the unknown action `{name}`")
+            }
+            BuildError::InvalidParams { path, name, reason } => {
+                write!(f, "`{path}` has invalid parameters for `{name}`: {reason}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for BuildError {}
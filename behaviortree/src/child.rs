@@ -1,14 +1,118 @@
-use crate::{Behavior, State};
-use crate::{Status, action_type::ActionType, behavior_nodes::*};
+use alloc::{boxed::Box, rc::Rc, string::String, vec::Vec};
+use core::{any::TypeId, cell::RefCell};
+
+use crate::{Behavior, NodeChildren, NodeId, NodeSnapshot, NodeState, SnapshotError, TreeObserver};
+#[cfg(feature = "metrics")]
+use crate::{Metrics, NodeMetrics};
+use crate::{
+    Status, SyncAction,
+    action_type::ActionType,
+    behavior_nodes::*,
+    path::{ROOT, child_path},
+};
+
+/// Observer shared by the nodes, `None` when the tree has no observer
+pub type ObserverRef = Option<Rc<RefCell<dyn TreeObserver>>>;
+
+/// Skips the `()` observer, so that nodes of a tree without observer never call it
+pub fn observer_ref<O: TreeObserver + 'static>(observer: &Rc<RefCell<O>>) -> ObserverRef {
+    if TypeId::of::<O>() == TypeId::of::<()>() {
+        return None;
+    }
+    Some(observer.clone())
+}
+
+/// Built-in nodes are kept concrete so that their state can be snapshotted
+enum Node<S> {
+    Action(ActionType<S>),
+    Wait(WaitState),
+    Invert(Box<InvertState<S>>),
+    Sequence(SequenceState<S>),
+    Select(SelectState<S>),
+}
+
+impl<S> Node<S> {
+    fn tick(&mut self, delta: f64, shared: &mut S) -> Status {
+        match self {
+            Node::Action(action) => action.tick(delta, shared),
+            Node::Wait(wait) => wait.tick(delta, shared),
+            Node::Invert(invert) => invert.tick(delta, shared),
+            Node::Sequence(sequence) => sequence.tick(delta, shared),
+            Node::Select(select) => select.tick(delta, shared),
+        }
+    }
+
+    fn reset(&mut self, shared: &mut S) {
+        match self {
+            Node::Action(action) => action.reset(shared),
+            Node::Wait(wait) => SyncAction::<S>::reset(wait, shared),
+            Node::Invert(invert) => invert.reset(shared),
+            Node::Sequence(sequence) => sequence.reset(shared),
+            Node::Select(select) => select.reset(shared),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Node::Action(action) => action.name(),
+            Node::Wait(wait) => SyncAction::<S>::name(wait),
+            Node::Invert(invert) => invert.name(),
+            Node::Sequence(sequence) => sequence.name(),
+            Node::Select(select) => select.name(),
+        }
+    }
+
+    fn children(&self) -> &[Child<S>] {
+        match self {
+            Node::Action(_) | Node::Wait(_) => &[],
+            Node::Invert(invert) => invert.children(),
+            Node::Sequence(sequence) => sequence.children(),
+            Node::Select(select) => select.children(),
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    fn children_mut(&mut self) -> &mut [Child<S>] {
+        match self {
+            Node::Action(_) | Node::Wait(_) => &mut [],
+            Node::Invert(invert) => invert.children_mut(),
+            Node::Sequence(sequence) => sequence.children_mut(),
+            Node::Select(select) => select.children_mut(),
+        }
+    }
+}
 
 pub struct Child<S> {
-    action: ActionType<S>,
-    status: tokio::sync::watch::Sender<Option<Status>>,
+    node: Node<S>,
+    id: NodeId,
+    /// i.e `root/Sequence[1]/Invert/Action`
+    path: String,
+    /// Display label of the node, see `Behavior::Named`
+    label: Option<String>,
+    status: Option<Status>,
+    #[cfg(feature = "metrics")]
+    metrics: NodeMetrics,
+    observer: ObserverRef,
 }
 
 impl<S> Child<S> {
-    pub fn new(action: ActionType<S>, status: tokio::sync::watch::Sender<Option<Status>>) -> Self {
-        Self { action, status }
+    fn new(
+        node: Node<S>,
+        id: NodeId,
+        path: String,
+        label: Option<String>,
+        observer: ObserverRef,
+    ) -> Self {
+        Self {
+            node,
+            id,
+            path,
+            label,
+            status: None,
+            #[cfg(feature = "metrics")]
+            metrics: NodeMetrics::default(),
+            observer,
+        }
     }
 
     #[cfg(test)]
@@ -17,108 +121,327 @@ impl<S> Child<S> {
         A: Into<ActionType<S>>,
         S: 'static,
     {
-        let (child, _state) = Self::from_behavior_with_state(behavior);
-        child
+        Self::from_behavior_with_observer(behavior, &None, &mut 0)
     }
 
-    #[cfg(test)]
-    pub fn from_behavior_with_state<A>(behavior: Behavior<A>) -> (Self, State)
+    /// Node ids are handed out from `next_id`, children before their parent
+    pub fn from_behavior_with_observer<A>(
+        behavior: Behavior<A>,
+        observer: &ObserverRef,
+        next_id: &mut usize,
+    ) -> Self
     where
         A: Into<ActionType<S>>,
         S: 'static,
     {
-        let mut statuses = vec![];
-        Self::from_behavior_with_state_and_status(behavior, &mut statuses)
+        Self::from_behavior_at(behavior, observer, next_id, ROOT, None, None)
     }
 
-    pub fn from_behavior_with_state_and_status<A>(
+    /// Builds the node attached to `parent` at `index`, see `child_path`
+    ///
+    /// `label` is the display label of the node, set by `Behavior::Named`.
+    fn from_behavior_at<A>(
         behavior: Behavior<A>,
-        statuses: &mut Vec<tokio::sync::watch::Sender<Option<Status>>>,
-    ) -> (Self, State)
+        observer: &ObserverRef,
+        next_id: &mut usize,
+        parent: &str,
+        index: Option<usize>,
+        label: Option<String>,
+    ) -> Self
     where
         A: Into<ActionType<S>>,
         S: 'static,
     {
-        match behavior {
+        let (node, children, path) = match behavior {
             Behavior::Action(action) => {
-                let action = action.into();
-
-                let (tx, rx) = tokio::sync::watch::channel(None);
-                statuses.push(tx.clone());
-
-                let state = State::NoChild(action.name(), rx);
-                (Self::new(action, tx), state)
+                let node = Node::Action(action.into());
+                let path = child_path(parent, index, node.name());
+                (node, Vec::new(), path)
             }
             Behavior::Wait(target) => {
-                let action = Box::new(WaitState::new(target));
-                let action = ActionType::Sync(action);
-
-                let (tx, rx) = tokio::sync::watch::channel(None);
-                statuses.push(tx.clone());
-
-                let state = State::NoChild(action.name(), rx);
-                (Self::new(action, tx), state)
+                let node = Node::Wait(WaitState::new(target));
+                let path = child_path(parent, index, node.name());
+                (node, Vec::new(), path)
             }
             Behavior::Invert(child) => {
-                let (child, child_state) =
-                    Self::from_behavior_with_state_and_status(*child, statuses);
-
-                let action = Box::new(InvertState::new(child));
-                let action = ActionType::Sync(action);
+                let path = child_path(parent, index, "Invert");
+                let child = Self::from_behavior_at(*child, observer, next_id, &path, None, None);
+                let child_id = child.id;
 
-                let (tx, rx) = tokio::sync::watch::channel(None);
-                statuses.push(tx.clone());
-
-                let state = State::SingleChild(action.name(), rx, child_state.into());
-                (Self::new(action, tx), state)
+                let node = Node::Invert(Box::new(InvertState::new(child)));
+                (node, Vec::from([child_id]), path)
             }
             Behavior::Sequence(children) => {
-                let (children, children_state): (Vec<_>, Vec<_>) = children
-                    .into_iter()
-                    .map(|child| Child::from_behavior_with_state_and_status(child, statuses))
-                    .unzip();
-                let children_state = std::rc::Rc::from_iter(children_state);
-
-                let action = Box::new(SequenceState::new(children));
-                let action = ActionType::Sync(action);
+                let path = child_path(parent, index, "Sequence");
+                let children = Self::from_behaviors(children, observer, next_id, &path);
+                let children_id = children.iter().map(|child| child.id).collect();
 
-                let (tx, rx) = tokio::sync::watch::channel(None);
-                statuses.push(tx.clone());
-
-                let state = State::MultipleChildren(action.name(), rx, children_state);
-                (Self::new(action, tx), state)
+                let node = Node::Sequence(SequenceState::new(children));
+                (node, children_id, path)
             }
             Behavior::Select(children) => {
-                let (children, children_state): (Vec<_>, Vec<_>) = children
-                    .into_iter()
-                    .map(|child| Child::from_behavior_with_state_and_status(child, statuses))
-                    .unzip();
-                let children_state = std::rc::Rc::from_iter(children_state);
-
-                let action = Box::new(SelectState::new(children));
-                let action = ActionType::Sync(action);
-
-                let (tx, rx) = tokio::sync::watch::channel(None);
-                statuses.push(tx.clone());
+                let path = child_path(parent, index, "Select");
+                let children = Self::from_behaviors(children, observer, next_id, &path);
+                let children_id = children.iter().map(|child| child.id).collect();
 
-                let state = State::MultipleChildren(action.name(), rx, children_state);
-                (Self::new(action, tx), state)
+                let node = Node::Select(SelectState::new(children));
+                (node, children_id, path)
             }
+            Behavior::Named(name, behavior) => {
+                // The outermost name wins
+                let label = label.or(Some(name));
+                return Self::from_behavior_at(*behavior, observer, next_id, parent, index, label);
+            }
+        };
+
+        let children = match &node {
+            Node::Action(_) | Node::Wait(_) => NodeChildren::None,
+            Node::Invert(_) => NodeChildren::Single(children[0]),
+            Node::Sequence(_) | Node::Select(_) => NodeChildren::Multiple(&children),
+        };
+        let id = NodeId(*next_id);
+        *next_id += 1;
+        if let Some(observer) = observer {
+            observer
+                .borrow_mut()
+                .on_node_added(id, node.name(), label.as_deref(), &path, children);
         }
+        Self::new(node, id, path, label, observer.clone())
+    }
+
+    fn from_behaviors<A>(
+        behaviors: Vec<Behavior<A>>,
+        observer: &ObserverRef,
+        next_id: &mut usize,
+        parent: &str,
+    ) -> Vec<Self>
+    where
+        A: Into<ActionType<S>>,
+        S: 'static,
+    {
+        behaviors
+            .into_iter()
+            .enumerate()
+            .map(|(index, child)| {
+                Self::from_behavior_at(child, observer, next_id, parent, Some(index), None)
+            })
+            .collect()
     }
 
     pub fn tick(&mut self, delta: f64, shared: &mut S) -> Status {
-        let status = self.action.tick(delta, shared);
-        self.status.send_replace(Some(status));
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "node",
+            otel.name = self.label().unwrap_or(self.name()),
+            name = self.node.name(),
+            path = %self.path,
+            status = tracing::field::Empty,
+        )
+        .entered();
+
+        #[cfg(all(feature = "metrics", feature = "std"))]
+        let start = std::time::Instant::now();
+        let status = self.node.tick(delta, shared);
+        #[cfg(all(feature = "metrics", feature = "std"))]
+        {
+            self.metrics.tick_time += start.elapsed();
+        }
+        #[cfg(feature = "metrics")]
+        self.metrics.record(delta, status);
+        #[cfg(feature = "tracing")]
+        span.record("status", tracing::field::debug(status));
+
+        let id = self.id;
+        self.notify(|observer| observer.on_node_ticked(id, status));
+        self.set_status(Some(status));
         status
     }
 
+    /// Ticks the root node, reporting `delta` to the observer first
+    pub fn tick_root(&mut self, delta: f64, shared: &mut S) -> Status {
+        self.notify(|observer| observer.on_tick(delta));
+        self.tick(delta, shared)
+    }
+
     pub fn reset(&mut self, shared: &mut S) {
-        self.action.reset(shared);
+        self.node.reset(shared);
+        self.set_status(None);
+    }
+
+    /// Resets the root node, reporting it to the observer first
+    pub fn reset_root(&mut self, shared: &mut S) {
+        self.notify(|observer| observer.on_reset());
+        self.reset(shared);
     }
 
     pub fn status(&self) -> Option<Status> {
-        *self.status.borrow()
+        self.status
+    }
+
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// The node `id` among this node and its children
+    pub fn find(&self, id: NodeId) -> Option<&Self> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.node.children().iter().find_map(|child| child.find(id))
+    }
+
+    /// Calls `f` with this node and its children, parents first
+    #[cfg(feature = "testing")]
+    pub fn visit(&self, f: &mut impl FnMut(&Self)) {
+        f(self);
+        for child in self.node.children() {
+            child.visit(f);
+        }
+    }
+
+    /// Adds the metrics of this node and its children to `metrics`
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self, metrics: &mut Metrics) {
+        metrics.insert(self.path.clone(), self.metrics);
+        for child in self.node.children() {
+            child.metrics(metrics);
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn reset_metrics(&mut self) {
+        self.metrics = NodeMetrics::default();
+        for child in self.node.children_mut() {
+            child.reset_metrics();
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.node.name()
+    }
+
+    pub fn snapshot(&self) -> NodeSnapshot {
+        let state = match &self.node {
+            Node::Action(action) => NodeState::Action(action.save()),
+            Node::Wait(wait) => wait.snapshot(),
+            Node::Invert(invert) => invert.snapshot(),
+            Node::Sequence(sequence) => sequence.snapshot(),
+            Node::Select(select) => select.snapshot(),
+        };
+        NodeSnapshot {
+            name: String::from(self.name()),
+            status: self.status,
+            state,
+        }
+    }
+
+    /// Restores the root node, reporting it to the observer first
+    pub fn restore_root(&mut self, snapshot: NodeSnapshot) -> Result<(), SnapshotError> {
+        self.notify(|observer| observer.on_restore());
+        let path = self.path.clone();
+        self.restore(snapshot, &path)
+    }
+
+    /// `path` identifies this node in errors
+    pub fn restore(&mut self, snapshot: NodeSnapshot, path: &str) -> Result<(), SnapshotError> {
+        if snapshot.name != self.name() {
+            return Err(SnapshotError::NameMismatch {
+                path: String::from(path),
+                expected: String::from(self.name()),
+                found: snapshot.name,
+            });
+        }
+
+        // Completed nodes are not ticked again, they must keep a final status
+        let completed = match &snapshot.state {
+            NodeState::Action(_) => None,
+            NodeState::Wait { completed, .. }
+            | NodeState::Invert { completed, .. }
+            | NodeState::Sequence { completed, .. }
+            | NodeState::Select { completed, .. } => Some(*completed),
+        };
+        let finished = matches!(snapshot.status, Some(Status::Success | Status::Failure));
+        if let Some(completed) = completed
+            && completed != finished
+        {
+            return Err(SnapshotError::InconsistentStatus {
+                path: String::from(path),
+                status: snapshot.status,
+                completed,
+            });
+        }
+
+        match (&mut self.node, snapshot.state) {
+            (Node::Action(action), NodeState::Action(state)) => {
+                if let Some(state) = &state {
+                    action.load(state).map_err(|reason| SnapshotError::Action {
+                        path: String::from(path),
+                        reason,
+                    })?;
+                }
+            }
+            (Node::Wait(wait), NodeState::Wait { elapsed, completed }) => {
+                wait.restore(elapsed, completed);
+            }
+            (Node::Invert(invert), NodeState::Invert { completed, child }) => {
+                invert.restore(completed, *child, path)?;
+            }
+            (
+                Node::Sequence(sequence),
+                NodeState::Sequence {
+                    index,
+                    completed,
+                    children,
+                },
+            ) => sequence.restore(index, completed, children, path)?,
+            (
+                Node::Select(select),
+                NodeState::Select {
+                    index,
+                    completed,
+                    children,
+                },
+            ) => select.restore(index, completed, children, path)?,
+            _ => {
+                return Err(SnapshotError::StructureMismatch {
+                    path: String::from(path),
+                });
+            }
+        }
+        self.set_status(snapshot.status);
+        Ok(())
+    }
+
+    fn set_status(&mut self, status: Option<Status>) {
+        let old = core::mem::replace(&mut self.status, status);
+        if old != status {
+            let id = self.id;
+            self.notify(|observer| observer.on_status_change(id, old, status));
+        }
+    }
+
+    fn notify(&self, f: impl FnOnce(&mut dyn TreeObserver)) {
+        if let Some(observer) = &self.observer {
+            f(&mut *observer.borrow_mut());
+        }
+    }
+}
+
+impl<S> Drop for Child<S> {
+    fn drop(&mut self) {
+        if let Some(observer) = &self.observer
+            && let Ok(mut observer) = observer.try_borrow_mut()
+        {
+            observer.on_node_removed(self.id);
+        }
     }
 }
 
@@ -126,6 +449,7 @@ impl<S> Child<S> {
 mod tests {
     use super::*;
     use crate::test_behavior_interface::{TestAction, TestShared};
+    use crate::test_observer::TestObserver;
 
     #[test]
     fn test_basic_behavior() {
@@ -137,7 +461,7 @@ mod tests {
             Behavior::Action(TestAction::Success),
         ]);
 
-        let (mut child, _state) = Child::from_behavior_with_state(behavior);
+        let mut child = Child::from_behavior(behavior);
         let mut shared = TestShared;
 
         loop {
@@ -147,4 +471,111 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_observer_ref() {
+        assert!(observer_ref(&Rc::new(RefCell::new(()))).is_none());
+        assert!(observer_ref(&Rc::new(RefCell::new(TestObserver::default()))).is_some());
+    }
+
+    #[test]
+    fn test_observer() {
+        let behavior =
+            Behavior::Invert(Behavior::Action(TestAction::SuccessAfter { times: 1 }).into());
+
+        let observer = Rc::new(RefCell::new(TestObserver::default()));
+        let mut child =
+            Child::from_behavior_with_observer(behavior, &observer_ref(&observer), &mut 0);
+        let mut shared = TestShared;
+        assert_eq!(observer.borrow().names, ["SuccessAfter1", "Invert"]);
+        assert_eq!(child.path(), "root/Invert");
+
+        assert_eq!(child.tick(1.0, &mut shared), Status::Running);
+        assert_eq!(child.tick(1.0, &mut shared), Status::Failure);
+        child.reset(&mut shared);
+
+        use Status::*;
+        assert_eq!(
+            observer.borrow().changes,
+            [
+                (0, None, Some(Running)),
+                (1, None, Some(Running)),
+                (0, Some(Running), Some(Success)),
+                (1, Some(Running), Some(Failure)),
+                (0, Some(Success), None),
+                (1, Some(Failure), None),
+            ]
+        );
+    }
+
+    #[cfg(all(feature = "tracing", feature = "std"))]
+    #[test]
+    fn test_tracing_spans() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{field::Field, span};
+
+        /// Path and status of every span, in creation order
+        #[derive(Clone, Default)]
+        struct Spans(Arc<Mutex<Vec<(String, String)>>>);
+
+        struct Fields<'a>(&'a mut (String, String));
+
+        impl tracing::field::Visit for Fields<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn core::fmt::Debug) {
+                match field.name() {
+                    "path" => self.0.0 = format!("{value:?}"),
+                    "status" => self.0.1 = format!("{value:?}"),
+                    _ => {}
+                }
+            }
+        }
+
+        impl tracing::Subscriber for Spans {
+            fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+                let mut spans = self.0.lock().unwrap();
+                let mut fields = Default::default();
+                span.record(&mut Fields(&mut fields));
+                spans.push(fields);
+                span::Id::from_u64(spans.len() as u64)
+            }
+
+            fn record(&self, span: &span::Id, values: &span::Record<'_>) {
+                let mut spans = self.0.lock().unwrap();
+                values.record(&mut Fields(&mut spans[span.into_u64() as usize - 1]));
+            }
+
+            fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+            fn event(&self, _event: &tracing::Event<'_>) {}
+
+            fn enter(&self, _span: &span::Id) {}
+
+            fn exit(&self, _span: &span::Id) {}
+        }
+
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Invert(Behavior::Action(TestAction::Failure).into()),
+        ]);
+        let mut child = Child::from_behavior(behavior);
+
+        let spans = Spans::default();
+        tracing::subscriber::with_default(spans.clone(), || {
+            while child.tick(1.0, &mut TestShared) == Status::Running {}
+        });
+        assert_eq!(
+            *spans.0.lock().unwrap(),
+            [
+                ("root/Sequence".into(), "Running".into()),
+                ("root/Sequence[0]/Success".into(), "Success".into()),
+                ("root/Sequence".into(), "Success".into()),
+                ("root/Sequence[1]/Invert".into(), "Success".into()),
+                ("root/Sequence[1]/Invert/Failure".into(), "Failure".into()),
+            ]
+        );
+    }
 }
@@ -0,0 +1,359 @@
+use alloc::{collections::BTreeMap, rc::Rc, string::String, vec::Vec};
+use core::cell::{Ref, RefCell};
+
+use crate::{ActionType, Behavior, BehaviorTree, NodeChildren, NodeId, Status, TreeObserver};
+
+/// Node of a breakpoint, by id or by path i.e `root/Sequence[1]/Invert/Action`
+///
+/// Breakpoints apply to the nodes of a replaced behavior by path,
+/// id breakpoints follow the path of their node once it has been removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BreakpointNode {
+    Id(NodeId),
+    Path(String),
+}
+
+impl From<NodeId> for BreakpointNode {
+    fn from(value: NodeId) -> Self {
+        Self::Id(value)
+    }
+}
+
+impl From<&str> for BreakpointNode {
+    fn from(value: &str) -> Self {
+        Self::Path(value.into())
+    }
+}
+
+impl From<String> for BreakpointNode {
+    fn from(value: String) -> Self {
+        Self::Path(value)
+    }
+}
+
+/// Status change that triggers a breakpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakOn {
+    /// The node is ticked for the first time since it was created or reset
+    Start,
+    /// The node changes to this status
+    Status(Status),
+    /// Any status change, resets included
+    Change,
+}
+
+impl BreakOn {
+    fn matches(self, old: Option<Status>, new: Option<Status>) -> bool {
+        match self {
+            BreakOn::Start => old.is_none() && new.is_some(),
+            BreakOn::Status(status) => new == Some(status),
+            BreakOn::Change => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BreakpointId(usize);
+
+/// Status change that triggered a breakpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakpointHit {
+    pub breakpoint: BreakpointId,
+    pub node_id: NodeId,
+    pub old: Option<Status>,
+    pub new: Option<Status>,
+}
+
+#[derive(Default)]
+struct Breakpoints {
+    breakpoints: BTreeMap<BreakpointId, (BreakpointNode, BreakOn)>,
+    next_id: usize,
+    /// Paths of the nodes of the tree
+    paths: BTreeMap<NodeId, String>,
+    hits: Vec<BreakpointHit>,
+}
+
+impl Breakpoints {
+    fn add(&mut self, node: BreakpointNode, on: BreakOn) -> BreakpointId {
+        let id = BreakpointId(self.next_id);
+        self.next_id += 1;
+        self.breakpoints.insert(id, (node, on));
+        id
+    }
+
+    /// Hits of the node are dropped, i.e the resets of a replaced behavior
+    fn on_node_removed(&mut self, node_id: NodeId) {
+        let Some(path) = self.paths.remove(&node_id) else {
+            return;
+        };
+        for (node, _) in self.breakpoints.values_mut() {
+            if *node == BreakpointNode::Id(node_id) {
+                *node = BreakpointNode::Path(path.clone());
+            }
+        }
+        self.hits.retain(|hit| hit.node_id != node_id);
+    }
+
+    fn on_status_change(&mut self, node_id: NodeId, old: Option<Status>, new: Option<Status>) {
+        let path = self.paths.get(&node_id);
+        for (&breakpoint, (node, on)) in &self.breakpoints {
+            let at_node = match node {
+                BreakpointNode::Id(id) => *id == node_id,
+                BreakpointNode::Path(node_path) => path == Some(node_path),
+            };
+            if at_node && on.matches(old, new) {
+                self.hits.push(BreakpointHit {
+                    breakpoint,
+                    node_id,
+                    old,
+                    new,
+                });
+            }
+        }
+    }
+}
+
+/// Observer of a [`Debugger`] tree, forwards every call to the wrapped observer
+pub struct DebugObserver<O> {
+    observer: O,
+    breakpoints: Rc<RefCell<Breakpoints>>,
+}
+
+impl<O> DebugObserver<O> {
+    pub fn inner(&self) -> &O {
+        &self.observer
+    }
+}
+
+impl<O: TreeObserver> TreeObserver for DebugObserver<O> {
+    fn on_node_added(
+        &mut self,
+        node_id: NodeId,
+        name: &str,
+        label: Option<&str>,
+        path: &str,
+        children: NodeChildren<'_>,
+    ) {
+        self.breakpoints
+            .borrow_mut()
+            .paths
+            .insert(node_id, path.into());
+        self.observer
+            .on_node_added(node_id, name, label, path, children);
+    }
+
+    fn on_node_removed(&mut self, node_id: NodeId) {
+        self.breakpoints.borrow_mut().on_node_removed(node_id);
+        self.observer.on_node_removed(node_id);
+    }
+
+    fn on_tick(&mut self, dt: f64) {
+        self.observer.on_tick(dt);
+    }
+
+    fn on_node_ticked(&mut self, node_id: NodeId, status: Status) {
+        self.observer.on_node_ticked(node_id, status);
+    }
+
+    fn on_reset(&mut self) {
+        self.observer.on_reset();
+    }
+
+    fn on_restore(&mut self) {
+        self.observer.on_restore();
+    }
+
+    fn on_status_change(&mut self, node_id: NodeId, old: Option<Status>, new: Option<Status>) {
+        self.breakpoints
+            .borrow_mut()
+            .on_status_change(node_id, old, new);
+        self.observer.on_status_change(node_id, old, new);
+    }
+}
+
+/// Why [`Debugger::step`] or [`Debugger::resume`] returned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebugEvent {
+    /// Breakpoints hit during the last tick, in order
+    Hit(Vec<BreakpointHit>),
+    /// The root node completed with this status
+    Completed(Status),
+    /// The tree is still running
+    Running,
+}
+
+/// Wraps a `BehaviorTree` to pause it on breakpoints.
+///
+/// A tick always runs to completion, the tree pauses in between:
+/// inspect `shared` after a [`DebugEvent::Hit`], then `step` or `resume`.
+pub struct Debugger<S, O = ()> {
+    tree: BehaviorTree<S, DebugObserver<O>>,
+    breakpoints: Rc<RefCell<Breakpoints>>,
+}
+
+impl<S, O> Debugger<S, O> {
+    pub fn new<A>(behavior: Behavior<A>, should_loop: bool, shared: S, observer: O) -> Self
+    where
+        A: Into<ActionType<S>>,
+        S: 'static,
+        O: TreeObserver + 'static,
+    {
+        let breakpoints = Rc::new(RefCell::new(Breakpoints::default()));
+        let observer = DebugObserver {
+            observer,
+            breakpoints: breakpoints.clone(),
+        };
+        Self {
+            tree: BehaviorTree::new(behavior, should_loop, shared, observer),
+            breakpoints,
+        }
+    }
+
+    /// Pauses the tree whenever `node` changes status as described by `on`
+    pub fn add_breakpoint(&mut self, node: impl Into<BreakpointNode>, on: BreakOn) -> BreakpointId {
+        self.breakpoints.borrow_mut().add(node.into(), on)
+    }
+
+    /// Returns `false` if the breakpoint had already been removed
+    pub fn remove_breakpoint(&mut self, id: BreakpointId) -> bool {
+        self.breakpoints
+            .borrow_mut()
+            .breakpoints
+            .remove(&id)
+            .is_some()
+    }
+
+    /// Ticks the tree once
+    pub fn step(&mut self, dt: f64) -> DebugEvent {
+        let status = self.tree.tick(dt);
+        let hits = core::mem::take(&mut self.breakpoints.borrow_mut().hits);
+        if !hits.is_empty() {
+            DebugEvent::Hit(hits)
+        } else if status != Status::Running {
+            DebugEvent::Completed(status)
+        } else {
+            DebugEvent::Running
+        }
+    }
+
+    /// Ticks the tree until a breakpoint is hit or the root node completes, at most `max_ticks` times
+    pub fn resume(&mut self, dt: f64, max_ticks: usize) -> DebugEvent {
+        for _ in 0..max_ticks {
+            let event = self.step(dt);
+            if event != DebugEvent::Running {
+                return event;
+            }
+        }
+        DebugEvent::Running
+    }
+
+    pub fn tree(&self) -> &BehaviorTree<S, DebugObserver<O>> {
+        &self.tree
+    }
+
+    /// i.e to `replace_behavior`, breakpoints are kept by path
+    pub fn tree_mut(&mut self) -> &mut BehaviorTree<S, DebugObserver<O>> {
+        &mut self.tree
+    }
+
+    pub fn observer(&self) -> Ref<'_, O> {
+        Ref::map(self.tree.observer(), DebugObserver::inner)
+    }
+
+    pub fn shared(&self) -> &S {
+        self.tree.shared()
+    }
+
+    pub fn shared_mut(&mut self) -> &mut S {
+        self.tree.shared_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, vec};
+
+    use super::*;
+    use crate::test_behavior_interface::{TestAction, TestShared};
+
+    #[test]
+    fn test_debugger() {
+        let behavior = || {
+            Behavior::Sequence(vec![
+                Behavior::Action(TestAction::SuccessAfter { times: 1 }),
+                Behavior::Invert(Box::new(Behavior::Action(TestAction::Failure))),
+            ])
+        };
+        let mut debugger = Debugger::new(behavior(), false, TestShared, ());
+        let start = debugger.add_breakpoint("root/Sequence[1]/Invert", BreakOn::Start);
+        let success = debugger.add_breakpoint(NodeId(0), BreakOn::Status(Status::Success));
+
+        assert_eq!(debugger.step(0.1), DebugEvent::Running);
+        assert_eq!(
+            debugger.resume(0.1, 10),
+            DebugEvent::Hit(vec![BreakpointHit {
+                breakpoint: success,
+                node_id: NodeId(0),
+                old: Some(Status::Running),
+                new: Some(Status::Success),
+            }])
+        );
+        assert_eq!(
+            debugger.resume(0.1, 10),
+            DebugEvent::Hit(vec![BreakpointHit {
+                breakpoint: start,
+                node_id: NodeId(2),
+                old: None,
+                new: Some(Status::Success),
+            }])
+        );
+        assert_eq!(debugger.tree().status(), Some(Status::Success));
+        assert_eq!(
+            debugger.resume(0.1, 10),
+            DebugEvent::Completed(Status::Success)
+        );
+
+        // Removed breakpoints don't apply to the new nodes
+        assert!(debugger.remove_breakpoint(success));
+        assert!(!debugger.remove_breakpoint(success));
+        debugger.tree_mut().replace_behavior(behavior());
+        assert_eq!(
+            debugger.resume(0.1, 10),
+            DebugEvent::Hit(vec![BreakpointHit {
+                breakpoint: start,
+                node_id: NodeId(6),
+                old: None,
+                new: Some(Status::Success),
+            }])
+        );
+    }
+
+    #[test]
+    fn test_debugger_replace_behavior() {
+        let behavior = || Behavior::Invert(Box::new(Behavior::Action(TestAction::Success)));
+        let mut debugger = Debugger::new(behavior(), false, TestShared, ());
+        let change = debugger.add_breakpoint(NodeId(0), BreakOn::Change);
+        let failure = debugger.add_breakpoint("root/Invert", BreakOn::Status(Status::Failure));
+        assert!(matches!(debugger.step(0.1), DebugEvent::Hit(hits) if hits.len() == 2));
+
+        // The resets of the old nodes are not reported, the id breakpoint follows its path
+        debugger.tree_mut().replace_behavior(behavior());
+        assert_eq!(
+            debugger.step(0.1),
+            DebugEvent::Hit(vec![
+                BreakpointHit {
+                    breakpoint: change,
+                    node_id: NodeId(2),
+                    old: None,
+                    new: Some(Status::Success),
+                },
+                BreakpointHit {
+                    breakpoint: failure,
+                    node_id: NodeId(3),
+                    old: None,
+                    new: Some(Status::Failure),
+                },
+            ])
+        );
+    }
+}
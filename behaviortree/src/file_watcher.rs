@@ -0,0 +1,82 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Polls a file for changes, i.e to reload a tree definition while it is edited.
+///
+/// ```no_run
+/// use behaviortree::FileWatcher;
+///
+/// let mut watcher = FileWatcher::new("tree.json");
+/// loop {
+///     if let Some(Ok(contents)) = watcher.poll() {
+///         // Parse `contents` and pass it to `BehaviorTree::replace_behavior`
+///     }
+///     // Tick the tree
+/// #   break;
+/// }
+/// ```
+pub struct FileWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl FileWatcher {
+    /// Changes are reported relative to the current contents of the file
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let modified = modified(&path).ok();
+        Self { path, modified }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the contents of the file when it has been modified since the last poll
+    ///
+    /// A modification that could not be read is reported once as an error.
+    pub fn poll(&mut self) -> Option<io::Result<String>> {
+        let modified = modified(&self.path).ok();
+        if modified.is_none() || modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(std::fs::read_to_string(&self.path))
+    }
+}
+
+fn modified(path: &Path) -> io::Result<SystemTime> {
+    std::fs::metadata(path)?.modified()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_file_watcher() {
+        let path = std::env::temp_dir().join(format!("behaviortree-{}.json", std::process::id()));
+        std::fs::write(&path, "first").unwrap();
+
+        let mut watcher = FileWatcher::new(&path);
+        assert!(watcher.poll().is_none());
+
+        std::fs::write(&path, "second").unwrap();
+        let modified = SystemTime::now() + Duration::from_secs(1);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert_eq!(watcher.poll().unwrap().unwrap(), "second");
+        assert!(watcher.poll().is_none());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(watcher.poll().is_none());
+    }
+}
@@ -1,12 +1,38 @@
+#![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
+
+extern crate alloc;
+
+#[macro_use]
+mod macros;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::{boxed::Box, vec};
+}
+
 mod behavior;
 pub use behavior::*;
 
+mod build_error;
+pub use build_error::*;
+
+mod builder;
+pub use builder::*;
+
 mod status;
 pub use status::*;
 
+#[cfg(feature = "observe")]
 mod state;
+#[cfg(feature = "observe")]
 pub use state::*;
 
+mod state_snapshot;
+pub use state_snapshot::*;
+
+mod observer;
+pub use observer::*;
+
 mod behavior_interface;
 pub use behavior_interface::*;
 
@@ -16,6 +42,46 @@ pub use action_type::*;
 mod behaviortree;
 pub use behaviortree::*;
 
+mod snapshot;
+pub use snapshot::*;
+
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "metrics")]
+pub use metrics::*;
+
+mod registry;
+pub use registry::*;
+
+mod trace;
+pub use trace::*;
+
+mod debugger;
+pub use debugger::*;
+
+#[cfg(feature = "monitor")]
+mod monitor;
+#[cfg(feature = "monitor")]
+pub use monitor::*;
+
+#[cfg(feature = "testing")]
+mod testing;
+#[cfg(feature = "testing")]
+pub use testing::*;
+
+#[cfg(feature = "std")]
+mod file_watcher;
+#[cfg(feature = "std")]
+pub use file_watcher::*;
+
+mod render;
+
+#[cfg(feature = "xml")]
+mod xml;
+#[cfg(feature = "xml")]
+pub use xml::*;
+
 // Not meant to be used externally
 mod behavior_nodes;
 mod child;
+mod path;
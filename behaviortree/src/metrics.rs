@@ -0,0 +1,30 @@
+use alloc::{collections::BTreeMap, string::String};
+
+use crate::Status;
+
+/// Counters of a single node, see `BehaviorTree::metrics`
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NodeMetrics {
+    pub ticks: u64,
+    pub successes: u64,
+    pub failures: u64,
+    /// Sum of the `delta` of every tick that returned `Running`, in delta units
+    pub running_time: f64,
+    /// Wall-clock time spent inside `tick`, children included
+    #[cfg(feature = "std")]
+    pub tick_time: core::time::Duration,
+}
+
+impl NodeMetrics {
+    pub(crate) fn record(&mut self, delta: f64, status: Status) {
+        self.ticks += 1;
+        match status {
+            Status::Success => self.successes += 1,
+            Status::Failure => self.failures += 1,
+            Status::Running => self.running_time += delta,
+        }
+    }
+}
+
+/// Metrics of every node keyed by path, i.e `root/Sequence[1]/Invert/Action`
+pub type Metrics = BTreeMap<String, NodeMetrics>;
@@ -0,0 +1,319 @@
+//! Streams a tree to remote viewers on localhost, see [`MonitorServer`].
+
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use tungstenite::handshake::server::{NoCallback, ServerHandshake};
+use tungstenite::handshake::{HandshakeError, MidHandshake};
+use tungstenite::{Message, WebSocket};
+
+use crate::{NodeId, State, StateDiff, StateSnapshot, Status};
+
+/// Version of the [`MonitorMessage`] protocol, sent in [`MonitorMessage::Hello`]
+///
+/// Bumped on every change that an existing viewer could not read.
+pub const MONITOR_PROTOCOL_VERSION: u32 = 1;
+
+/// Slow viewers are disconnected rather than stalling the tree
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// WebSocket connections that don't complete their handshake in time are dropped
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Messages sent by a [`MonitorServer`], as JSON objects tagged by `type`
+///
+/// A connection starts with `Hello` and `Tree`, followed by `Statuses` and `Tree`
+/// whenever the tree changes.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MonitorMessage {
+    /// `{"type":"hello","version":1}`
+    Hello { version: u32 },
+    /// Every node of the tree, sent on connection and when the tree is replaced
+    Tree { tree: StateSnapshot },
+    /// `{"type":"statuses","statuses":[[0,"Success"],[3,null]]}`, nodes whose status changed in id order
+    Statuses {
+        statuses: Vec<(NodeId, Option<Status>)>,
+    },
+}
+
+impl From<StateDiff> for MonitorMessage {
+    fn from(diff: StateDiff) -> Self {
+        match diff {
+            StateDiff::Statuses(statuses) => Self::Statuses { statuses },
+            StateDiff::Tree(tree) => Self::Tree { tree },
+        }
+    }
+}
+
+type Handshake = MidHandshake<ServerHandshake<TcpStream, NoCallback>>;
+
+/// Handshake in progress, dropped after its deadline
+type PendingHandshake = (Handshake, Instant);
+
+enum Client {
+    /// One message per line
+    Tcp(TcpStream),
+    /// One message per text frame
+    WebSocket(Box<WebSocket<TcpStream>>),
+}
+
+impl Client {
+    fn stream(&self) -> &TcpStream {
+        match self {
+            Client::Tcp(stream) => stream,
+            Client::WebSocket(socket) => socket.get_ref(),
+        }
+    }
+
+    fn send(&mut self, json: &str) -> io::Result<()> {
+        match self {
+            Client::Tcp(stream) => {
+                stream.write_all(json.as_bytes())?;
+                stream.write_all(b"\n")
+            }
+            Client::WebSocket(socket) => socket.send(Message::text(json)).map_err(io::Error::other),
+        }
+    }
+}
+
+/// Serves the nodes of a `State` and their status changes to the viewers connected on `addr`.
+///
+/// Nothing runs in the background: [`MonitorServer::publish`] accepts the new viewers and
+/// sends them the changes since its last call, i.e after every `BehaviorTree::tick`.
+///
+/// ```no_run
+/// use behaviortree::MonitorServer;
+///
+/// let mut server = MonitorServer::bind("127.0.0.1:7878").unwrap();
+/// loop {
+///     // Tick a `BehaviorTree` observed by a `StateObserver`, then
+///     // server.publish(&tree.state());
+/// #   break;
+/// }
+/// ```
+pub struct MonitorServer {
+    listener: TcpListener,
+    websocket: bool,
+    clients: Vec<Client>,
+    handshakes: Vec<PendingHandshake>,
+    snapshot: Option<StateSnapshot>,
+}
+
+impl MonitorServer {
+    /// Streams the messages as JSON lines over plain TCP
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::new(addr, false)
+    }
+
+    /// Streams the messages as WebSocket text frames, i.e to a browser
+    pub fn bind_websocket(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::new(addr, true)
+    }
+
+    fn new(addr: impl ToSocketAddrs, websocket: bool) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            websocket,
+            clients: Vec::new(),
+            handshakes: Vec::new(),
+            snapshot: None,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Number of connected viewers
+    pub fn clients(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Sends the changes of `state` since the last call, then accepts the pending viewers
+    ///
+    /// Viewers that can't keep up are disconnected, connections that fail are dropped.
+    pub fn publish(&mut self, state: &State) {
+        let snapshot = state.snapshot();
+        if let Some(previous) = &self.snapshot {
+            let diff = previous.diff(&snapshot);
+            if !diff.is_empty() {
+                let json = to_json(&diff.into());
+                self.clients.retain_mut(|client| client.send(&json).is_ok());
+            }
+        }
+
+        let hello = to_json(&MonitorMessage::Hello {
+            version: MONITOR_PROTOCOL_VERSION,
+        });
+        let tree = to_json(&MonitorMessage::Tree {
+            tree: snapshot.clone(),
+        });
+        // Handshakes are read without blocking, messages are written with a timeout
+        let mut welcome = |mut client: Client| {
+            if client.stream().set_nonblocking(false).is_ok()
+                && client.send(&hello).is_ok()
+                && client.send(&tree).is_ok()
+            {
+                self.clients.push(client);
+            }
+        };
+
+        let now = Instant::now();
+        for (handshake, deadline) in core::mem::take(&mut self.handshakes) {
+            if deadline < now {
+                continue;
+            }
+            match handshake.handshake() {
+                Ok(socket) => welcome(Client::WebSocket(Box::new(socket))),
+                Err(HandshakeError::Interrupted(handshake)) => {
+                    self.handshakes.push((handshake, deadline));
+                }
+                Err(HandshakeError::Failure(_)) => {}
+            }
+        }
+
+        // Other errors than `WouldBlock` are retried on the next call, i.e once a descriptor is free
+        while let Ok((stream, _)) = self.listener.accept() {
+            let configured = stream
+                .set_nodelay(true)
+                .and_then(|()| stream.set_write_timeout(Some(WRITE_TIMEOUT)))
+                .and_then(|()| stream.set_nonblocking(self.websocket));
+            if configured.is_err() {
+                continue;
+            }
+            if !self.websocket {
+                welcome(Client::Tcp(stream));
+                continue;
+            }
+            match tungstenite::accept(stream) {
+                Ok(socket) => welcome(Client::WebSocket(Box::new(socket))),
+                Err(HandshakeError::Interrupted(handshake)) => {
+                    self.handshakes.push((handshake, now + HANDSHAKE_TIMEOUT));
+                }
+                Err(HandshakeError::Failure(_)) => {}
+            }
+        }
+
+        self.snapshot = Some(snapshot);
+    }
+}
+
+fn to_json(message: &MonitorMessage) -> String {
+    serde_json::to_string(message).expect("messages serialize to JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+
+    use super::*;
+    use crate::test_behavior_interface::{TestAction, TestShared};
+    use crate::{Behavior, BehaviorTree, StateObserver};
+
+    fn behavior() -> Behavior<TestAction> {
+        Behavior::Sequence(vec![
+            Behavior::Action(TestAction::Success),
+            Behavior::Action(TestAction::Success),
+        ])
+    }
+
+    #[test]
+    fn test_monitor_server_tcp() {
+        let mut tree = BehaviorTree::new(behavior(), false, TestShared, StateObserver::default());
+        let mut server = MonitorServer::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        let mut lines = BufReader::new(client).lines();
+        let mut read = move || -> MonitorMessage {
+            serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap()
+        };
+
+        server.publish(&tree.state());
+        assert_eq!(server.clients(), 1);
+        assert_eq!(read(), MonitorMessage::Hello { version: 1 });
+        assert_eq!(
+            read(),
+            MonitorMessage::Tree {
+                tree: tree.state().snapshot()
+            }
+        );
+
+        // Unchanged trees send nothing
+        server.publish(&tree.state());
+        tree.tick(0.1);
+        server.publish(&tree.state());
+        assert_eq!(
+            read(),
+            MonitorMessage::Statuses {
+                statuses: vec![
+                    (NodeId(0), Some(Status::Success)),
+                    (NodeId(2), Some(Status::Running))
+                ]
+            }
+        );
+
+        tree.replace_behavior(Behavior::<TestAction>::Wait(1.0));
+        server.publish(&tree.state());
+        let MonitorMessage::Tree { tree: snapshot } = read() else {
+            panic!("expected the replaced tree");
+        };
+        assert_eq!(snapshot.id, NodeId(3));
+        assert_eq!(snapshot.name, "Wait");
+    }
+
+    #[test]
+    fn test_monitor_server_websocket() {
+        let mut tree = BehaviorTree::new(behavior(), false, TestShared, StateObserver::default());
+        let mut server = MonitorServer::bind_websocket("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        let viewer = std::thread::spawn(move || {
+            let (mut socket, _) = tungstenite::connect(url).unwrap();
+            (0..3)
+                .map(|_| {
+                    let text = socket.read().unwrap().into_text().unwrap();
+                    serde_json::from_str::<MonitorMessage>(&text).unwrap()
+                })
+                .collect::<Vec<_>>()
+        });
+
+        while server.clients() == 0 {
+            server.publish(&tree.state());
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        tree.tick(0.1);
+        server.publish(&tree.state());
+
+        let messages = viewer.join().unwrap();
+        assert_eq!(messages[0], MonitorMessage::Hello { version: 1 });
+        assert!(matches!(&messages[1], MonitorMessage::Tree { tree } if tree.status.is_none()));
+        assert_eq!(
+            messages[2],
+            MonitorMessage::Statuses {
+                statuses: vec![
+                    (NodeId(0), Some(Status::Success)),
+                    (NodeId(2), Some(Status::Running))
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_monitor_server_handshake_timeout() {
+        let tree = BehaviorTree::new(behavior(), false, TestShared, StateObserver::default());
+        let mut server = MonitorServer::bind_websocket("127.0.0.1:0").unwrap();
+        // Connects without ever sending the handshake
+        let _client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        while server.handshakes.is_empty() {
+            server.publish(&tree.state());
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        server.handshakes[0].1 = Instant::now() - Duration::from_secs(1);
+        server.publish(&tree.state());
+        assert!(server.handshakes.is_empty());
+        assert_eq!(server.clients(), 0);
+    }
+}
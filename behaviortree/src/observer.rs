@@ -0,0 +1,145 @@
+use crate::Status;
+
+/// Identifies a node of a tree.
+///
+/// Ids are handed out while the tree is built, children before their parent,
+/// and are never reused by the tree or by a replaced behavior.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(transparent)]
+pub struct NodeId(pub usize);
+
+impl core::fmt::Display for NodeId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Shape of a node, as reported to [`TreeObserver::on_node_added`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeChildren<'a> {
+    None,
+    Single(NodeId),
+    Multiple(&'a [NodeId]),
+}
+
+/// Receives the status changes of every node in a tree.
+///
+/// Plugged into `BehaviorTree::new`, use `()` when nothing needs to be observed.
+pub trait TreeObserver {
+    /// Called once for every node while the tree is built.
+    ///
+    /// Children are always added before their parent, the root node is added last.
+    /// Ids are never reused, a replaced behavior is added with fresh ids
+    /// before the nodes of the previous one are removed.
+    /// `label` is the display label given by `Behavior::Named`, and
+    /// `path` locates the node from the root, i.e `root/Sequence[1]/Invert/Action`.
+    fn on_node_added(
+        &mut self,
+        _node_id: NodeId,
+        _name: &str,
+        _label: Option<&str>,
+        _path: &str,
+        _children: NodeChildren<'_>,
+    ) {
+    }
+
+    /// Called when a node is dropped along with its tree.
+    fn on_node_removed(&mut self, _node_id: NodeId) {}
+
+    /// Called before the root node is ticked with `dt`.
+    fn on_tick(&mut self, _dt: f64) {}
+
+    /// Called every time a node is ticked, before its status changes.
+    fn on_node_ticked(&mut self, _node_id: NodeId, _status: Status) {}
+
+    /// Called before the tree is reset, by `BehaviorTree::reset` or when a looping tree starts over.
+    fn on_reset(&mut self) {}
+
+    /// Called before a snapshot is restored with `BehaviorTree::restore`.
+    fn on_restore(&mut self) {}
+
+    /// Called whenever the status of a node changes.
+    ///
+    /// `None` means that the node has not been ticked since it was created or reset.
+    fn on_status_change(&mut self, node_id: NodeId, old: Option<Status>, new: Option<Status>);
+}
+
+impl TreeObserver for () {
+    fn on_status_change(&mut self, _node_id: NodeId, _old: Option<Status>, _new: Option<Status>) {}
+}
+
+/// Forwards every call to both observers, i.e to record a trace while publishing `State`.
+impl<A: TreeObserver, B: TreeObserver> TreeObserver for (A, B) {
+    fn on_node_added(
+        &mut self,
+        node_id: NodeId,
+        name: &str,
+        label: Option<&str>,
+        path: &str,
+        children: NodeChildren<'_>,
+    ) {
+        self.0.on_node_added(node_id, name, label, path, children);
+        self.1.on_node_added(node_id, name, label, path, children);
+    }
+
+    fn on_node_removed(&mut self, node_id: NodeId) {
+        self.0.on_node_removed(node_id);
+        self.1.on_node_removed(node_id);
+    }
+
+    fn on_tick(&mut self, dt: f64) {
+        self.0.on_tick(dt);
+        self.1.on_tick(dt);
+    }
+
+    fn on_node_ticked(&mut self, node_id: NodeId, status: Status) {
+        self.0.on_node_ticked(node_id, status);
+        self.1.on_node_ticked(node_id, status);
+    }
+
+    fn on_reset(&mut self) {
+        self.0.on_reset();
+        self.1.on_reset();
+    }
+
+    fn on_restore(&mut self) {
+        self.0.on_restore();
+        self.1.on_restore();
+    }
+
+    fn on_status_change(&mut self, node_id: NodeId, old: Option<Status>, new: Option<Status>) {
+        self.0.on_status_change(node_id, old, new);
+        self.1.on_status_change(node_id, old, new);
+    }
+}
+
+#[cfg(test)]
+pub mod test_observer {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct TestObserver {
+        pub names: Vec<String>,
+        pub changes: Vec<(usize, Option<Status>, Option<Status>)>,
+    }
+
+    impl TreeObserver for TestObserver {
+        fn on_node_added(
+            &mut self,
+            node_id: NodeId,
+            name: &str,
+            _label: Option<&str>,
+            _path: &str,
+            _children: NodeChildren<'_>,
+        ) {
+            assert_eq!(node_id.0, self.names.len());
+            self.names.push(name.into());
+        }
+
+        fn on_status_change(&mut self, node_id: NodeId, old: Option<Status>, new: Option<Status>) {
+            self.changes.push((node_id.0, old, new));
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use alloc::{format, string::String};
+
+/// Path that every root node is attached to
+pub const ROOT: &str = "root";
+
+/// Path of a node named `name` below the node at `parent`.
+///
+/// Children of control nodes carry their `index` on the parent segment,
+/// i.e `root/Sequence[1]/Invert/Action`.
+pub fn child_path(parent: &str, index: Option<usize>, name: &str) -> String {
+    match index {
+        Some(index) => format!("{parent}[{index}]/{name}"),
+        None => format!("{parent}/{name}"),
+    }
+}
@@ -0,0 +1,257 @@
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec, vec::Vec};
+
+use crate::{
+    ActionType, Behavior, BuildError,
+    path::{ROOT, child_path},
+};
+
+/// Refers to an action of an [`ActionRegistry`] by name.
+///
+/// Serialized as `{"name": "MoveTo", "params": {..}}`, where `params` is any
+/// deserializable type such as `serde_json::Value`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ActionSpec<P> {
+    pub name: String,
+    pub params: P,
+}
+
+type Factory<S, P> = Box<dyn Fn(&P) -> Result<ActionType<S>, String>>;
+
+/// Builds actions from their name and parameters, for trees loaded from data.
+///
+/// ```
+/// use behaviortree::{ActionRegistry, ActionSpec, ActionType, Behavior, ImmediateAction};
+///
+/// struct Log(String);
+///
+/// impl ImmediateAction<()> for Log {
+///     fn run(&mut self, _delta: f64, _shared: &mut ()) -> bool {
+///         println!("{}", self.0);
+///         true
+///     }
+///
+///     fn reset(&mut self, _shared: &mut ()) {}
+///
+///     fn name(&self) -> &'static str {
+///         "Log"
+///     }
+/// }
+///
+/// let mut registry = ActionRegistry::new();
+/// registry.register("Log", |message: &String| {
+///     Ok(ActionType::Immediate(Box::new(Log(message.clone()))))
+/// });
+///
+/// let behavior = Behavior::Action(ActionSpec {
+///     name: "Log".to_owned(),
+///     params: "Hello".to_owned(),
+/// });
+/// assert!(registry.resolve(behavior).is_ok());
+/// ```
+pub struct ActionRegistry<S, P> {
+    factories: BTreeMap<String, Factory<S, P>>,
+}
+
+impl<S, P> Default for ActionRegistry<S, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, P> ActionRegistry<S, P> {
+    pub fn new() -> Self {
+        Self {
+            factories: BTreeMap::new(),
+        }
+    }
+
+    /// Registers the factory of the `name` action, replacing any previous one
+    ///
+    /// The factory returns an error message when it rejects the parameters.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        factory: impl Fn(&P) -> Result<ActionType<S>, String> + 'static,
+    ) -> &mut Self {
+        self.factories.insert(name.into(), Box::new(factory));
+        self
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    /// Builds every action of `behavior`, the result can be passed to `BehaviorTree::new`
+    ///
+    /// Reports unknown actions and rejected parameters along with the problems
+    /// found by [`Behavior::validate`]. Error paths name actions after their spec.
+    pub fn resolve(
+        &self,
+        behavior: Behavior<ActionSpec<P>>,
+    ) -> Result<Behavior<ActionType<S>>, Vec<BuildError>> {
+        let mut errors = match behavior.validate() {
+            Ok(()) => vec![],
+            Err(errors) => errors,
+        };
+        let path = child_path(ROOT, None, node_name(&behavior));
+        let behavior = self.resolve_at(behavior, path, &mut errors);
+        match behavior {
+            Some(behavior) if errors.is_empty() => Ok(behavior),
+            _ => Err(errors),
+        }
+    }
+
+    fn resolve_at(
+        &self,
+        behavior: Behavior<ActionSpec<P>>,
+        path: String,
+        errors: &mut Vec<BuildError>,
+    ) -> Option<Behavior<ActionType<S>>> {
+        let resolve_children = |children: Vec<Behavior<ActionSpec<P>>>,
+                                errors: &mut Vec<BuildError>| {
+            let children: Vec<_> = children
+                .into_iter()
+                .enumerate()
+                .map(|(index, child)| {
+                    let path = child_path(&path, Some(index), node_name(&child));
+                    self.resolve_at(child, path, errors)
+                })
+                .collect();
+            children.into_iter().collect::<Option<Vec<_>>>()
+        };
+
+        match behavior {
+            Behavior::Action(spec) => {
+                let Some(factory) = self.factories.get(&spec.name) else {
+                    errors.push(BuildError::UnknownAction {
+                        path,
+                        name: spec.name,
+                    });
+                    return None;
+                };
+                match factory(&spec.params) {
+                    Ok(action) => Some(Behavior::Action(action)),
+                    Err(reason) => {
+                        errors.push(BuildError::InvalidParams {
+                            path,
+                            name: spec.name,
+                            reason,
+                        });
+                        None
+                    }
+                }
+            }
+            Behavior::Wait(target) => Some(Behavior::Wait(target)),
+            Behavior::Invert(child) => {
+                let path = child_path(&path, None, node_name(&child));
+                let child = self.resolve_at(*child, path, errors)?;
+                Some(Behavior::Invert(Box::new(child)))
+            }
+            Behavior::Sequence(children) => {
+                resolve_children(children, errors).map(Behavior::Sequence)
+            }
+            Behavior::Select(children) => resolve_children(children, errors).map(Behavior::Select),
+            Behavior::Named(label, behavior) => {
+                let behavior = self.resolve_at(*behavior, path, errors)?;
+                Some(Behavior::Named(label, Box::new(behavior)))
+            }
+        }
+    }
+}
+
+/// Name of the node built from `behavior` in error paths, actions are named after their spec
+///
+/// The runtime path uses the `name()` of the built action instead, so the two differ
+/// when a factory builds an action with another name.
+fn node_name<P>(behavior: &Behavior<ActionSpec<P>>) -> &str {
+    match behavior {
+        Behavior::Action(spec) => &spec.name,
+        Behavior::Named(_, behavior) => node_name(behavior),
+        behavior => behavior.kind_name(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::{BehaviorTree, ImmediateAction, Status};
+
+    #[derive(Default)]
+    struct Counter {
+        count: u64,
+    }
+
+    struct Add(u64);
+
+    impl ImmediateAction<Counter> for Add {
+        fn run(&mut self, _delta: f64, shared: &mut Counter) -> bool {
+            shared.count += self.0;
+            true
+        }
+
+        fn reset(&mut self, _shared: &mut Counter) {}
+
+        fn name(&self) -> &'static str {
+            "Add"
+        }
+    }
+
+    fn registry() -> ActionRegistry<Counter, Value> {
+        let mut registry = ActionRegistry::new();
+        registry.register("Add", |params: &Value| {
+            let amount = params["amount"].as_u64().ok_or("expected an `amount`")?;
+            Ok(ActionType::Immediate(Box::new(Add(amount))))
+        });
+        registry
+    }
+
+    #[test]
+    fn test_registry_resolve() {
+        let behavior: Behavior<ActionSpec<Value>> = serde_json::from_value(json!({
+            "Sequence": [
+                { "Action": { "name": "Add", "params": { "amount": 2 } } },
+                { "Wait": 1.0 },
+                { "Action": { "name": "Add", "params": { "amount": 3 } } },
+            ]
+        }))
+        .unwrap();
+
+        let behavior = registry().resolve(behavior).ok().unwrap();
+        let mut tree = BehaviorTree::new(behavior, false, Counter::default(), ());
+        while tree.tick(1.0) == Status::Running {}
+        assert_eq!(tree.status(), Some(Status::Success));
+    }
+
+    #[test]
+    fn test_registry_errors() {
+        let behavior: Behavior<ActionSpec<Value>> = serde_json::from_value(json!({
+            "Select": [
+                { "Action": { "name": "Add", "params": {} } },
+                { "Invert": { "Action": { "name": "MoveTo", "params": null } } },
+                { "Sequence": [] },
+            ]
+        }))
+        .unwrap();
+
+        let errors = registry().resolve(behavior).err().unwrap();
+        assert_eq!(
+            errors,
+            [
+                BuildError::EmptyComposite {
+                    path: "root/Select[2]/Sequence".into()
+                },
+                BuildError::InvalidParams {
+                    path: "root/Select[0]/Add".into(),
+                    name: "Add".into(),
+                    reason: "expected an `amount`".into()
+                },
+                BuildError::UnknownAction {
+                    path: "root/Select[1]/Invert/MoveTo".into(),
+                    name: "MoveTo".into()
+                },
+            ]
+        );
+    }
+}
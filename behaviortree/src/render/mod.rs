@@ -0,0 +1,55 @@
+//! Text renderings of a `Behavior` structure or of a live `State`.
+
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::Debug;
+
+#[cfg(feature = "observe")]
+use crate::State;
+use crate::{Behavior, Status};
+
+mod ascii;
+mod dot;
+mod mermaid;
+
+/// Node of a tree to render, built from a `Behavior` or a `State`
+struct RenderNode {
+    label: String,
+    status: Option<Status>,
+    children: Vec<RenderNode>,
+}
+
+impl RenderNode {
+    fn from_behavior<A: Debug>(behavior: &Behavior<A>) -> Self {
+        let (label, children) = match behavior {
+            Behavior::Action(action) => (format!("{action:?}"), Vec::new()),
+            Behavior::Wait(target) => (format!("Wait({target})"), Vec::new()),
+            Behavior::Invert(child) => (
+                String::from(behavior.kind_name()),
+                Vec::from([Self::from_behavior(child)]),
+            ),
+            Behavior::Sequence(children) | Behavior::Select(children) => (
+                String::from(behavior.kind_name()),
+                children.iter().map(Self::from_behavior).collect(),
+            ),
+            Behavior::Named(label, behavior) => {
+                let node = Self::from_behavior(behavior);
+                (label.clone(), node.children)
+            }
+        };
+        Self {
+            label,
+            status: None,
+            children,
+        }
+    }
+
+    #[cfg(feature = "observe")]
+    fn from_state(state: &State) -> Self {
+        let node = state.node();
+        Self {
+            label: String::from(&**node.label.as_ref().unwrap_or(&node.name)),
+            status: *state.status().borrow(),
+            children: state.children().iter().map(Self::from_state).collect(),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use crate::Status;
+
+/// Execution state of a node and its children, see `BehaviorTree::snapshot`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NodeSnapshot {
+    pub name: String,
+    pub status: Option<Status>,
+    pub state: NodeState,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum NodeState {
+    /// State saved by the action, `None` when it has nothing to save.
+    Action(Option<Vec<u8>>),
+    Wait {
+        elapsed: f64,
+        completed: bool,
+    },
+    Invert {
+        completed: bool,
+        child: Box<NodeSnapshot>,
+    },
+    Sequence {
+        index: usize,
+        completed: bool,
+        children: Vec<NodeSnapshot>,
+    },
+    Select {
+        index: usize,
+        completed: bool,
+        children: Vec<NodeSnapshot>,
+    },
+}
+
+/// A snapshot that cannot be restored into a tree.
+///
+/// Nodes are identified by the path of their names, i.e `root/Sequence[1]/Invert/Wait`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotError {
+    /// The snapshot has a different node than the tree.
+    NameMismatch {
+        path: String,
+        expected: String,
+        found: String,
+    },
+    /// The snapshot has a different kind of node, or a different number of children.
+    StructureMismatch { path: String },
+    /// The saved state is out of range, i.e a child index past the last child.
+    InvalidState { path: String },
+    /// The saved status contradicts the state, i.e a completed node still `Running`.
+    InconsistentStatus {
+        path: String,
+        status: Option<Status>,
+        completed: bool,
+    },
+    /// The action rejected its saved state.
+    Action { path: String, reason: String },
+}
+
+impl core::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SnapshotError::NameMismatch {
+                path,
+                expected,
+                found,
+            } => write!(f, "`{path}` expected `{expected}`, found `{found}`"),
+            SnapshotError::StructureMismatch { path } => {
+                write!(f, "`{path}` does not match the structure of the tree")
+            }
+            SnapshotError::InvalidState { path } => write!(f, "`{path}` has an invalid state"),
+            SnapshotError::InconsistentStatus {
+                path,
+                status,
+                completed,
+            } => write!(
+                f,
+                "`{path}` has the status {status:?} but `completed` is {completed}"
+            ),
+            SnapshotError::Action { path, reason } => {
+                write!(f, "`{path}` could not load its state: {reason}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for SnapshotError {}
@@ -1,20 +1,97 @@
-use crate::Status;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use crate::{NodeChildren, NodeId, StateSnapshot, Status, TreeObserver};
+
+/// Identifies the node of a `State`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeInfo {
+    pub id: NodeId,
+    pub name: Rc<str>,
+    /// Display label given by `Behavior::Named`
+    pub label: Option<Rc<str>>,
+    /// i.e `root/Sequence[1]/Invert/Action`
+    pub path: Rc<str>,
+}
 
 #[derive(Clone)]
 pub enum State {
-    NoChild(&'static str, tokio::sync::watch::Receiver<Option<Status>>),
+    NoChild(NodeInfo, tokio::sync::watch::Receiver<Option<Status>>),
     SingleChild(
-        &'static str,
+        NodeInfo,
         tokio::sync::watch::Receiver<Option<Status>>,
-        std::rc::Rc<State>,
+        Rc<State>,
     ),
     MultipleChildren(
-        &'static str,
+        NodeInfo,
         tokio::sync::watch::Receiver<Option<Status>>,
-        std::rc::Rc<[State]>,
+        Rc<[State]>,
     ),
 }
 
+impl State {
+    pub fn node(&self) -> &NodeInfo {
+        match self {
+            Self::NoChild(node, _)
+            | Self::SingleChild(node, _, _)
+            | Self::MultipleChildren(node, _, _) => node,
+        }
+    }
+
+    pub fn status(&self) -> &tokio::sync::watch::Receiver<Option<Status>> {
+        match self {
+            Self::NoChild(_, status)
+            | Self::SingleChild(_, status, _)
+            | Self::MultipleChildren(_, status, _) => status,
+        }
+    }
+
+    pub fn children(&self) -> &[State] {
+        match self {
+            Self::NoChild(..) => &[],
+            Self::SingleChild(_, _, child) => core::slice::from_ref(&**child),
+            Self::MultipleChildren(_, _, children) => children,
+        }
+    }
+
+    /// State of the node `id` in this tree
+    pub fn find(&self, id: NodeId) -> Option<&State> {
+        if self.node().id == id {
+            return Some(self);
+        }
+        self.children().iter().find_map(|child| child.find(id))
+    }
+
+    /// Plain copy of the tree with the current statuses, see [`StateSnapshot`]
+    pub fn snapshot(&self) -> StateSnapshot {
+        let node = self.node();
+        StateSnapshot {
+            id: node.id,
+            name: node.name.to_string(),
+            label: node.label.as_deref().map(String::from),
+            path: node.path.to_string(),
+            status: *self.status().borrow(),
+            children: self.children().iter().map(State::snapshot).collect(),
+        }
+    }
+}
+
+/// Yields `(node_id, status)` for every status change of the nodes of a tree, in the order they happened.
+///
+/// Every change is kept, i.e a looping tree yields its reset before the statuses of the next tick.
+/// Changes are queued until [`StateChanges::drain`] is called, i.e after every `BehaviorTree::tick`.
+/// Nodes added by `BehaviorTree::replace_behavior` are followed as well.
+pub struct StateChanges {
+    receiver: tokio::sync::mpsc::UnboundedReceiver<(NodeId, Option<Status>)>,
+}
+
+impl StateChanges {
+    /// Changes queued since the last call
+    pub fn drain(&mut self) -> impl Iterator<Item = (NodeId, Option<Status>)> + '_ {
+        std::iter::from_fn(|| self.receiver.try_recv().ok())
+    }
+}
+
 impl std::fmt::Debug for State {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -38,3 +115,135 @@ impl std::fmt::Debug for State {
         }
     }
 }
+
+/// Publishes the status of every node through a `State` tree of watch channels.
+#[derive(Default)]
+pub struct StateObserver {
+    /// Nodes of the current tree, removed nodes are dropped
+    statuses: BTreeMap<NodeId, tokio::sync::watch::Sender<Option<Status>>>,
+    /// Nodes without a parent yet, only the root once the tree is built
+    states: BTreeMap<NodeId, State>,
+    /// Queues of the [`StateChanges`], fed until every node has been removed
+    subscribers: Vec<tokio::sync::mpsc::UnboundedSender<(NodeId, Option<Status>)>>,
+}
+
+impl StateObserver {
+    /// State of the root node, of the latest tree when the behavior has been replaced
+    ///
+    /// Panics if no tree has been built with this observer yet.
+    pub fn state(&self) -> State {
+        self.states
+            .last_key_value()
+            .map(|(_, state)| state.clone())
+            .expect("StateObserver has not observed a tree")
+    }
+
+    /// Last status of the node `id`, `None` once the node has been removed
+    pub fn node_status(&self, id: NodeId) -> Option<Status> {
+        *self.statuses.get(&id)?.borrow()
+    }
+
+    /// Status changes of every observed node, see [`StateChanges`]
+    ///
+    /// Only changes made after this call are yielded, nodes added later included.
+    pub fn changes(&mut self) -> StateChanges {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        self.subscribers.push(sender);
+        StateChanges { receiver }
+    }
+
+    fn take_state(&mut self, node_id: NodeId) -> State {
+        self.states.remove(&node_id).unwrap()
+    }
+}
+
+impl TreeObserver for StateObserver {
+    fn on_node_added(
+        &mut self,
+        node_id: NodeId,
+        name: &str,
+        label: Option<&str>,
+        path: &str,
+        children: NodeChildren<'_>,
+    ) {
+        let (tx, rx) = tokio::sync::watch::channel(None);
+        let node = NodeInfo {
+            id: node_id,
+            name: name.into(),
+            label: label.map(Rc::from),
+            path: path.into(),
+        };
+        let state = match children {
+            NodeChildren::None => State::NoChild(node, rx),
+            NodeChildren::Single(child) => {
+                State::SingleChild(node, rx, self.take_state(child).into())
+            }
+            NodeChildren::Multiple(children) => {
+                let children_state = children
+                    .iter()
+                    .map(|child| self.take_state(*child))
+                    .collect();
+                State::MultipleChildren(node, rx, children_state)
+            }
+        };
+        self.statuses.insert(node_id, tx);
+        self.states.insert(node_id, state);
+    }
+
+    fn on_node_removed(&mut self, node_id: NodeId) {
+        // Receivers keep the last status but observe that the channel has closed
+        self.statuses.remove(&node_id);
+        self.states.remove(&node_id);
+        if self.statuses.is_empty() {
+            self.subscribers.clear();
+        }
+    }
+
+    fn on_status_change(&mut self, node_id: NodeId, _old: Option<Status>, new: Option<Status>) {
+        if let Some(status) = self.statuses.get(&node_id) {
+            status.send_replace(new);
+        }
+        // Dropped `StateChanges` are unsubscribed
+        self.subscribers
+            .retain(|sender| sender.send((node_id, new)).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_observer_drops_removed_nodes() {
+        let mut observer = StateObserver::default();
+        // Replaces an `Invert` around an action three times
+        for tree in 0..3 {
+            let (child, root) = (NodeId(2 * tree), NodeId(2 * tree + 1));
+            if let Some(previous) = root.0.checked_sub(2) {
+                observer.on_node_removed(NodeId(previous));
+                observer.on_node_removed(NodeId(previous - 1));
+            }
+            observer.on_node_added(
+                child,
+                "Success",
+                None,
+                "root/Invert/Success",
+                NodeChildren::None,
+            );
+            observer.on_node_added(
+                root,
+                "Invert",
+                None,
+                "root/Invert",
+                NodeChildren::Single(child),
+            );
+            observer.on_status_change(child, None, Some(Status::Success));
+        }
+
+        assert_eq!(observer.statuses.len(), 2);
+        assert_eq!(observer.states.len(), 1);
+        assert_eq!(observer.state().node().id, NodeId(5));
+        assert_eq!(observer.node_status(NodeId(4)), Some(Status::Success));
+        assert_eq!(observer.node_status(NodeId(2)), None);
+    }
+}
@@ -0,0 +1,266 @@
+//! Scripted actions and a harness to test trees, see [`MockAction`] and [`TreeHarness`].
+
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{ActionType, Behavior, BehaviorTree, Status, SyncAction, TreeObserver};
+
+/// Action returning a scripted sequence of statuses, one per tick.
+///
+/// Ticks past the end of the script repeat its last status, an empty script keeps running.
+/// The script starts over when the action is reset.
+///
+/// ```
+/// use behaviortree::{Behavior, MockAction, TreeHarness};
+///
+/// // Running x3 then Success
+/// let behavior = Behavior::Action(MockAction::new("MoveTo").running(3).success());
+/// let mut harness = TreeHarness::new(behavior, false, (), ());
+/// harness.run(0.1, 4);
+/// harness.assert_timeline("root/MoveTo", "R R R S");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockAction {
+    name: String,
+    script: Vec<Status>,
+}
+
+impl MockAction {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            script: Vec::new(),
+        }
+    }
+
+    /// Appends `times` ticks returning `status`
+    pub fn then(mut self, status: Status, times: usize) -> Self {
+        self.script.extend(core::iter::repeat_n(status, times));
+        self
+    }
+
+    pub fn running(self, times: usize) -> Self {
+        self.then(Status::Running, times)
+    }
+
+    pub fn success(self) -> Self {
+        self.then(Status::Success, 1)
+    }
+
+    pub fn failure(self) -> Self {
+        self.then(Status::Failure, 1)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn script(&self) -> &[Status] {
+        &self.script
+    }
+}
+
+struct MockState {
+    action: MockAction,
+    ticks: usize,
+}
+
+impl<S> SyncAction<S> for MockState {
+    fn tick(&mut self, _dt: f64, _shared: &mut S) -> Status {
+        let script = &self.action.script;
+        let status = script
+            .get(self.ticks)
+            .or(script.last())
+            .copied()
+            .unwrap_or(Status::Running);
+        self.ticks += 1;
+        status
+    }
+
+    fn reset(&mut self, _shared: &mut S) {
+        self.ticks = 0;
+    }
+
+    fn save(&self) -> Option<Vec<u8>> {
+        Some(self.ticks.to_le_bytes().to_vec())
+    }
+
+    fn load(&mut self, state: &[u8]) -> Result<(), String> {
+        let ticks = state.try_into().map_err(|_| "expected 8 bytes")?;
+        self.ticks = usize::from_le_bytes(ticks);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.action.name
+    }
+}
+
+impl<S> From<MockAction> for ActionType<S> {
+    fn from(action: MockAction) -> Self {
+        ActionType::Sync(Box::new(MockState { action, ticks: 0 }))
+    }
+}
+
+/// Ticks a `BehaviorTree` and records the status of every node after each tick.
+///
+/// Timelines are keyed by node path, i.e `root/Sequence[1]/Invert/Action`, and written as
+/// whitespace separated `S`, `F`, `R` or `-` (idle) by [`TreeHarness::assert_timeline`].
+pub struct TreeHarness<S, O = ()> {
+    tree: BehaviorTree<S, O>,
+    timelines: BTreeMap<String, Vec<Option<Status>>>,
+}
+
+impl<S, O> TreeHarness<S, O> {
+    pub fn new<A>(behavior: Behavior<A>, should_loop: bool, shared: S, observer: O) -> Self
+    where
+        A: Into<ActionType<S>>,
+        S: 'static,
+        O: TreeObserver + 'static,
+    {
+        Self {
+            tree: BehaviorTree::new(behavior, should_loop, shared, observer),
+            timelines: BTreeMap::new(),
+        }
+    }
+
+    pub fn tick(&mut self, dt: f64) -> Status {
+        let status = self.tree.tick(dt);
+        let timelines = &mut self.timelines;
+        self.tree
+            .for_each_node(|path, status| match timelines.get_mut(path) {
+                Some(timeline) => timeline.push(status),
+                None => {
+                    timelines.insert(path.to_string(), alloc::vec![status]);
+                }
+            });
+        status
+    }
+
+    /// Ticks the tree `ticks` times, returns the last status of the root node
+    pub fn run(&mut self, dt: f64, ticks: usize) -> Option<Status> {
+        for _ in 0..ticks {
+            self.tick(dt);
+        }
+        self.tree.status()
+    }
+
+    /// Statuses of the node at `path` after every tick since it was added
+    pub fn timeline(&self, path: &str) -> Option<&[Option<Status>]> {
+        self.timelines.get(path).map(Vec::as_slice)
+    }
+
+    /// Timelines of every node, by path
+    pub fn timelines(&self) -> impl Iterator<Item = (&str, &[Option<Status>])> {
+        self.timelines
+            .iter()
+            .map(|(path, timeline)| (path.as_str(), timeline.as_slice()))
+    }
+
+    /// Panics unless the timeline of the node at `path` is `expected`, i.e `"R R S"`
+    #[track_caller]
+    pub fn assert_timeline(&self, path: &str, expected: &str) {
+        let Some(timeline) = self.timeline(path) else {
+            let paths: Vec<_> = self.timelines.keys().map(String::as_str).collect();
+            panic!("no node at `{path}`, known paths are {paths:?}");
+        };
+        let found = format_timeline(timeline);
+        let expected = format_timeline(&parse_timeline(expected));
+        assert!(
+            found == expected,
+            "timeline of `{path}` differs\n expected: {expected}\n    found: {found}"
+        );
+    }
+
+    pub fn tree(&self) -> &BehaviorTree<S, O> {
+        &self.tree
+    }
+
+    /// i.e to `replace_behavior`, the timelines of the new nodes start at the next tick
+    pub fn tree_mut(&mut self) -> &mut BehaviorTree<S, O> {
+        &mut self.tree
+    }
+}
+
+#[track_caller]
+fn parse_timeline(timeline: &str) -> Vec<Option<Status>> {
+    timeline
+        .split_whitespace()
+        .map(|status| match status {
+            "S" => Some(Status::Success),
+            "F" => Some(Status::Failure),
+            "R" => Some(Status::Running),
+            "-" => None,
+            _ => panic!("unknown status `{status}`, expected S, F, R or -"),
+        })
+        .collect()
+}
+
+fn format_timeline(timeline: &[Option<Status>]) -> String {
+    let statuses: Vec<_> = timeline
+        .iter()
+        .map(|status| match status {
+            Some(Status::Success) => "S",
+            Some(Status::Failure) => "F",
+            Some(Status::Running) => "R",
+            None => "-",
+        })
+        .collect();
+    statuses.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_mock_action() {
+        let mut action: ActionType<()> = MockAction::new("Move").running(2).failure().into();
+        assert_eq!(action.name(), "Move");
+        let statuses: Vec<_> = (0..4).map(|_| action.tick(0.1, &mut ())).collect();
+        assert_eq!(
+            statuses,
+            [
+                Status::Running,
+                Status::Running,
+                Status::Failure,
+                Status::Failure
+            ]
+        );
+        action.reset(&mut ());
+        assert_eq!(action.tick(0.1, &mut ()), Status::Running);
+
+        let mut action: ActionType<()> = MockAction::new("Idle").into();
+        assert_eq!(action.tick(0.1, &mut ()), Status::Running);
+    }
+
+    #[test]
+    fn test_tree_harness() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(MockAction::new("Move").running(2).success()),
+            Behavior::Action(MockAction::new("Say").success()),
+        ]);
+        let mut harness = TreeHarness::new(behavior, true, (), ());
+        assert_eq!(harness.run(0.1, 5), Some(Status::Running));
+
+        harness.assert_timeline("root/Sequence", "R R R S R");
+        harness.assert_timeline("root/Sequence[0]/Move", "R R S S R");
+        harness.assert_timeline("root/Sequence[1]/Say", "- - - S -");
+        assert_eq!(harness.timeline("root/Wait"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "timeline of `root/Move` differs")]
+    fn test_tree_harness_mismatch() {
+        let behavior = Behavior::Action(MockAction::new("Move").running(1).success());
+        let mut harness = TreeHarness::new(behavior, false, (), ());
+        harness.run(0.1, 2);
+        harness.assert_timeline("root/Move", "R R");
+    }
+}
@@ -0,0 +1,426 @@
+use alloc::{
+    boxed::Box,
+    collections::{BTreeSet, VecDeque},
+    string::String,
+    vec::Vec,
+};
+
+use crate::{
+    ActionType, Behavior, BehaviorTree, NodeChildren, NodeId, Status, SyncAction, TreeObserver,
+};
+
+/// Node of a recorded tree, `children` are the ids of its children
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TraceNode {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub children: Vec<NodeId>,
+}
+
+/// Everything that happened during a recorded run, in order
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum TraceEvent {
+    /// `BehaviorTree::tick` ticked the root node with `dt`
+    Tick(f64),
+    /// An action returned `status`
+    Action(NodeId, Status),
+    /// The status of a node changed
+    Status(NodeId, Option<Status>),
+    /// The tree was reset, by `BehaviorTree::reset` or because it loops
+    Reset,
+    /// The tree was replaced by the tree whose root is the node
+    Replace(NodeId),
+    /// A snapshot was restored, such traces can't be replayed
+    Restore,
+}
+
+/// A trace that can't be replayed, see [`Trace::replay`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceError {
+    /// An event or a node refers to a node that was not recorded.
+    UnknownNode(NodeId),
+    /// A node that is not a child of a later node, or the child of several nodes.
+    InvalidTree(NodeId),
+    /// The restored state of the nodes was not recorded.
+    Restored,
+}
+
+impl core::fmt::Display for TraceError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TraceError::UnknownNode(id) => write!(f, "unknown node {id}"),
+            TraceError::InvalidTree(id) => write!(f, "node {id} is not part of a valid tree"),
+            TraceError::Restored => write!(f, "a snapshot was restored during the run"),
+        }
+    }
+}
+
+impl core::error::Error for TraceError {}
+
+/// Run of a `BehaviorTree` recorded by a [`TraceRecorder`].
+///
+/// Serialize it with any serde format to store it, i.e `postcard` for a compact trace file.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Trace {
+    /// Nodes indexed by id, the root node of every tree comes after its children
+    pub nodes: Vec<TraceNode>,
+    pub events: Vec<TraceEvent>,
+}
+
+/// Records a [`Trace`], must be passed to `BehaviorTree::new` so that every node is recorded.
+///
+/// Combine it with another observer using a tuple, i.e `(StateObserver::default(), TraceRecorder::default())`.
+#[derive(Debug, Default)]
+pub struct TraceRecorder {
+    trace: Trace,
+    /// Nodes without a parent, the root of the current tree and of its replacement
+    roots: BTreeSet<NodeId>,
+}
+
+impl TraceRecorder {
+    pub fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
+    pub fn into_trace(self) -> Trace {
+        self.trace
+    }
+}
+
+impl TreeObserver for TraceRecorder {
+    fn on_node_added(
+        &mut self,
+        node_id: NodeId,
+        name: &str,
+        label: Option<&str>,
+        _path: &str,
+        children: NodeChildren<'_>,
+    ) {
+        debug_assert_eq!(node_id.0, self.trace.nodes.len());
+        let children = match children {
+            NodeChildren::None => Vec::new(),
+            NodeChildren::Single(child) => alloc::vec![child],
+            NodeChildren::Multiple(children) => children.to_vec(),
+        };
+        for child in &children {
+            self.roots.remove(child);
+        }
+        self.roots.insert(node_id);
+        self.trace.nodes.push(TraceNode {
+            name: name.into(),
+            label: label.map(String::from),
+            children,
+        });
+    }
+
+    fn on_node_removed(&mut self, node_id: NodeId) {
+        // The previous root is removed right after its replacement is added
+        if self.roots.remove(&node_id)
+            && let Some(&root) = self.roots.last()
+        {
+            self.trace.events.push(TraceEvent::Replace(root));
+        }
+    }
+
+    fn on_tick(&mut self, dt: f64) {
+        self.trace.events.push(TraceEvent::Tick(dt));
+    }
+
+    fn on_reset(&mut self) {
+        self.trace.events.push(TraceEvent::Reset);
+    }
+
+    fn on_restore(&mut self) {
+        self.trace.events.push(TraceEvent::Restore);
+    }
+
+    fn on_node_ticked(&mut self, node_id: NodeId, status: Status) {
+        let is_action = self
+            .trace
+            .nodes
+            .get(node_id.0)
+            .is_some_and(|node| node.children.is_empty());
+        if is_action {
+            self.trace.events.push(TraceEvent::Action(node_id, status));
+        }
+    }
+
+    fn on_status_change(&mut self, node_id: NodeId, _old: Option<Status>, new: Option<Status>) {
+        self.trace.events.push(TraceEvent::Status(node_id, new));
+    }
+}
+
+/// Stands in for a recorded action, returning its recorded results in order.
+struct ReplayAction {
+    name: String,
+    results: VecDeque<Status>,
+}
+
+impl SyncAction<()> for ReplayAction {
+    /// Returns `Status::Failure` once the recorded results run out
+    fn tick(&mut self, _delta: f64, _shared: &mut ()) -> Status {
+        self.results.pop_front().unwrap_or(Status::Failure)
+    }
+
+    // The recorded results already account for resets
+    fn reset(&mut self, _shared: &mut ()) {}
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl From<ReplayAction> for ActionType<()> {
+    fn from(value: ReplayAction) -> Self {
+        ActionType::Sync(Box::new(value))
+    }
+}
+
+/// Recorded calls to a tree other than the status changes, replayed in order
+enum Step {
+    Tick(f64),
+    Reset,
+    Replace(Behavior<ReplayAction>),
+}
+
+/// Drives a `BehaviorTree` rebuilt from a [`Trace`], see [`Trace::replay`].
+pub struct Replay<O> {
+    tree: BehaviorTree<(), O>,
+    steps: VecDeque<Step>,
+}
+
+impl<O: TreeObserver + 'static> Replay<O> {
+    /// Ticks the tree with the next recorded `dt`, `None` once every tick has been replayed
+    ///
+    /// Resets and replacements recorded before the tick are replayed first.
+    pub fn step(&mut self) -> Option<Status> {
+        while let Some(step) = self.steps.pop_front() {
+            match step {
+                Step::Tick(dt) => return Some(self.tree.tick(dt)),
+                Step::Reset => self.tree.reset(),
+                Step::Replace(behavior) => self.tree.replace_behavior(behavior),
+            }
+        }
+        None
+    }
+}
+
+impl<O> Replay<O> {
+    pub fn tree(&self) -> &BehaviorTree<(), O> {
+        &self.tree
+    }
+}
+
+impl Trace {
+    /// Rebuilds the recorded tree, with every action replaced by its recorded results.
+    ///
+    /// Stepping through the replay reports the same status changes to `observer`
+    /// as the recorded run, i.e the same `State` sequence with a `StateObserver`.
+    /// Resets and replaced behaviors are replayed as recorded, so the tree does not loop on its own.
+    /// Traces of runs that restored a snapshot are rejected.
+    pub fn replay<O: TreeObserver + 'static>(&self, observer: O) -> Result<Replay<O>, TraceError> {
+        let roots = self.roots()?;
+        let mut results = alloc::vec![VecDeque::new(); self.nodes.len()];
+        for event in &self.events {
+            match *event {
+                TraceEvent::Action(node_id, status) => match results.get_mut(node_id.0) {
+                    Some(results) => results.push_back(status),
+                    None => return Err(TraceError::UnknownNode(node_id)),
+                },
+                TraceEvent::Status(node_id, _) if node_id.0 >= self.nodes.len() => {
+                    return Err(TraceError::UnknownNode(node_id));
+                }
+                TraceEvent::Replace(node_id) if !roots.contains(&node_id) => {
+                    return Err(TraceError::InvalidTree(node_id));
+                }
+                TraceEvent::Restore => return Err(TraceError::Restored),
+                _ => {}
+            }
+        }
+
+        let mut steps = VecDeque::new();
+        for event in &self.events {
+            match *event {
+                TraceEvent::Tick(dt) => steps.push_back(Step::Tick(dt)),
+                TraceEvent::Reset => steps.push_back(Step::Reset),
+                TraceEvent::Replace(root) => {
+                    steps.push_back(Step::Replace(self.behavior(root, &mut results)));
+                }
+                TraceEvent::Action(..) | TraceEvent::Status(..) | TraceEvent::Restore => {}
+            }
+        }
+
+        // Trees are recorded in order, the first one has the lowest root
+        let behavior = match roots.first() {
+            Some(&root) => self.behavior(root, &mut results),
+            None => Behavior::Sequence(Vec::new()),
+        };
+        Ok(Replay {
+            tree: BehaviorTree::new(behavior, false, (), observer),
+            steps,
+        })
+    }
+
+    /// Roots of the recorded trees, after checking that the nodes form trees
+    fn roots(&self) -> Result<BTreeSet<NodeId>, TraceError> {
+        let mut has_parent = alloc::vec![false; self.nodes.len()];
+        for (id, node) in self.nodes.iter().enumerate() {
+            for &child in &node.children {
+                // Children are recorded before their parent, which also rules out cycles
+                let Some(has_parent) = has_parent.get_mut(child.0) else {
+                    return Err(TraceError::UnknownNode(child));
+                };
+                if child.0 >= id || *has_parent {
+                    return Err(TraceError::InvalidTree(child));
+                }
+                *has_parent = true;
+            }
+        }
+        Ok((0..self.nodes.len())
+            .filter(|&id| !has_parent[id])
+            .map(NodeId)
+            .collect())
+    }
+
+    fn behavior(
+        &self,
+        node_id: NodeId,
+        results: &mut [VecDeque<Status>],
+    ) -> Behavior<ReplayAction> {
+        let node = &self.nodes[node_id.0];
+        let mut children = node
+            .children
+            .iter()
+            .map(|&child| self.behavior(child, results));
+        let behavior = match (node.name.as_str(), node.children.len()) {
+            (_, 0) => Behavior::Action(ReplayAction {
+                name: node.name.clone(),
+                results: core::mem::take(&mut results[node_id.0]),
+            }),
+            ("Invert", 1) => Behavior::Invert(Box::new(children.next().unwrap())),
+            ("Select", _) => Behavior::Select(children.collect()),
+            _ => Behavior::Sequence(children.collect()),
+        };
+        match &node.label {
+            Some(label) => Behavior::Named(label.clone(), Box::new(behavior)),
+            None => behavior,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::test_behavior_interface::{TestAction, TestShared};
+
+    #[test]
+    fn test_trace_replay() {
+        let behavior = Behavior::Select(vec![
+            Behavior::Sequence(vec![
+                Behavior::Action(TestAction::Success),
+                Behavior::Wait(1.5),
+                Behavior::Invert(Box::new(Behavior::Action(TestAction::SuccessAfter {
+                    times: 1,
+                }))),
+            ]),
+            Behavior::Action(TestAction::FailureAfter { times: 2 }),
+        ]);
+
+        let mut tree = BehaviorTree::new(behavior, true, TestShared, TraceRecorder::default());
+        for dt in [0.5, 1.0, 0.25, 1.0, 1.0, 1.0, 0.5, 2.0, 1.0, 1.0] {
+            tree.tick(dt);
+        }
+        let trace = tree.observer().trace().clone();
+        assert_eq!(trace.nodes.len(), 7);
+        assert_eq!(trace.nodes[6].name, "Select");
+        assert_eq!(trace.nodes[0].name, "Success");
+        assert_eq!(trace.events[0], TraceEvent::Tick(0.5));
+        assert!(
+            trace
+                .events
+                .contains(&TraceEvent::Action(NodeId(1), Status::Running))
+        );
+
+        let json = serde_json::to_string(&trace).unwrap();
+        let trace: Trace = serde_json::from_str(&json).unwrap();
+
+        let mut replay = trace.replay(TraceRecorder::default()).unwrap();
+        let mut steps = 0;
+        while replay.step().is_some() {
+            steps += 1;
+        }
+        assert_eq!(steps, 10);
+        assert_eq!(replay.tree().status(), tree.status());
+        assert_eq!(*replay.tree().observer().trace(), trace);
+    }
+
+    #[test]
+    fn test_trace_replay_reset_and_replace() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::SuccessAfter { times: 1 }),
+            Behavior::Wait(1.0),
+        ]);
+        let mut tree = BehaviorTree::new(behavior, false, TestShared, TraceRecorder::default());
+        tree.tick(1.0);
+        tree.reset();
+        tree.tick(1.0);
+        tree.tick(1.0);
+        tree.replace_behavior(Behavior::Invert(Box::new(Behavior::Action(
+            TestAction::Failure,
+        ))));
+        tree.tick(1.0);
+        tree.tick(1.0);
+
+        let trace = tree.observer().trace().clone();
+        assert!(trace.events.contains(&TraceEvent::Reset));
+        assert!(trace.events.contains(&TraceEvent::Replace(NodeId(4))));
+
+        let mut replay = trace.replay(TraceRecorder::default()).unwrap();
+        while replay.step().is_some() {}
+        assert_eq!(replay.tree().status(), Some(Status::Success));
+        assert_eq!(*replay.tree().observer().trace(), trace);
+    }
+
+    #[test]
+    fn test_trace_replay_errors() {
+        let behavior = Behavior::Invert(Box::new(Behavior::Action(TestAction::Success)));
+        let mut tree = BehaviorTree::new(behavior, false, TestShared, TraceRecorder::default());
+        tree.tick(1.0);
+        let snapshot = tree.snapshot();
+        let trace = tree.observer().trace().clone();
+
+        let mut unknown = trace.clone();
+        unknown
+            .events
+            .push(TraceEvent::Action(NodeId(7), Status::Success));
+        assert_eq!(
+            unknown.replay(()).err(),
+            Some(TraceError::UnknownNode(NodeId(7)))
+        );
+
+        let mut cycle = trace.clone();
+        cycle.nodes[0].children.push(NodeId(1));
+        assert_eq!(
+            cycle.replay(()).err(),
+            Some(TraceError::InvalidTree(NodeId(1)))
+        );
+
+        let mut shared = trace.clone();
+        shared.nodes.push(TraceNode {
+            name: "Sequence".into(),
+            label: None,
+            children: vec![NodeId(0)],
+        });
+        assert_eq!(
+            shared.replay(()).err(),
+            Some(TraceError::InvalidTree(NodeId(0)))
+        );
+
+        tree.restore(snapshot).unwrap();
+        let restored = tree.observer().trace().clone();
+        assert_eq!(restored.replay(()).err(), Some(TraceError::Restored));
+    }
+}
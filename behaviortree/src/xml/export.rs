@@ -0,0 +1,411 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+    vec::Vec,
+};
+use core::fmt::Write;
+
+use crate::{Behavior, behavior::is_valid_wait};
+
+/// Describes how an action is written by [`Behavior::to_btcpp_xml`].
+pub trait XmlAction {
+    /// Tag of the node, i.e `Say` for `<Say message="hello"/>`
+    fn xml_tag(&self) -> &str;
+
+    /// Attributes of the node, i.e `[("message", "hello")]` for `<Say message="hello"/>`
+    fn xml_attributes(&self) -> Vec<(&str, String)> {
+        Vec::new()
+    }
+}
+
+/// A tree that can't be written in the BehaviorTree.CPP format.
+#[derive(Debug, Clone, PartialEq)]
+pub enum XmlExportError {
+    /// A `Wait` that is not a whole, non-negative number of milliseconds, `Sleep` takes
+    /// unsigned milliseconds.
+    InvalidWait { target: f64 },
+    /// An action tag or attribute name that is not a valid XML name.
+    InvalidName(String),
+    /// An action tag that BehaviorTree.CPP reads as a built-in node, i.e `Sequence`.
+    ReservedTag(String),
+    /// A `Behavior::Named` around an action that already has a `name` attribute.
+    DuplicateName { tag: String, label: String },
+}
+
+impl core::fmt::Display for XmlExportError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            XmlExportError::InvalidWait { target } => {
+                write!(
+                    f,
+                    "`Sleep` must wait a whole non-negative number of milliseconds, found {target}"
+                )
+            }
+            XmlExportError::InvalidName(name) => write!(f, "`{name}` is not a valid XML name"),
+            XmlExportError::ReservedTag(tag) => write!(f, "`{tag}` is the tag of a built-in node"),
+            XmlExportError::DuplicateName { tag, label } => {
+                write!(f, "`{tag}` has a `name` attribute and the label `{label}`")
+            }
+        }
+    }
+}
+
+impl core::error::Error for XmlExportError {}
+
+/// Tag to the port names of every action, written to `TreeNodesModel`
+type Models<'a> = BTreeMap<&'a str, BTreeSet<&'a str>>;
+
+struct Writer<'a> {
+    xml: String,
+    models: Models<'a>,
+    errors: Vec<XmlExportError>,
+}
+
+impl<A: XmlAction> Behavior<A> {
+    /// Writes the tree in the [BehaviorTree.CPP](https://www.behaviortree.dev) v4 XML format.
+    ///
+    /// The tree is written as the `Main` tree, along with a `TreeNodesModel` describing
+    /// every action so that editors such as Groot can open it.
+    /// `Wait` times must be whole milliseconds, they are not rounded.
+    ///
+    /// ```
+    /// use behaviortree::{Behavior, XmlAction};
+    ///
+    /// struct IsDoorOpen;
+    ///
+    /// impl XmlAction for IsDoorOpen {
+    ///     fn xml_tag(&self) -> &str {
+    ///         "IsDoorOpen"
+    ///     }
+    /// }
+    ///
+    /// let behavior = Behavior::Select(vec![Behavior::Action(IsDoorOpen), Behavior::Wait(500.0)]);
+    /// assert!(behavior.to_btcpp_xml().unwrap().contains(r#"<Sleep msec="500"/>"#));
+    /// ```
+    pub fn to_btcpp_xml(&self) -> Result<String, Vec<XmlExportError>> {
+        let mut writer = Writer {
+            xml: String::new(),
+            models: Models::new(),
+            errors: Vec::new(),
+        };
+        writer.xml += "<root BTCPP_format=\"4\" main_tree_to_execute=\"Main\">\n";
+        writer.xml += "  <BehaviorTree ID=\"Main\">\n";
+        self.write_xml(&mut writer, 2, None);
+        writer.xml += "  </BehaviorTree>\n";
+
+        let Writer {
+            mut xml,
+            models,
+            errors,
+        } = writer;
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        xml += "  <TreeNodesModel>\n";
+        for (tag, ports) in models {
+            if ports.is_empty() {
+                let _ = writeln!(xml, "    <Action ID=\"{}\"/>", Escape(tag));
+                continue;
+            }
+            let _ = writeln!(xml, "    <Action ID=\"{}\">", Escape(tag));
+            for port in ports {
+                let _ = writeln!(xml, "      <input_port name=\"{}\"/>", Escape(port));
+            }
+            xml += "    </Action>\n";
+        }
+        xml += "  </TreeNodesModel>\n";
+        xml += "</root>\n";
+        Ok(xml)
+    }
+
+    /// `name` is the label of an enclosing `Behavior::Named`, written as the `name` attribute
+    fn write_xml<'a>(&'a self, writer: &mut Writer<'a>, depth: usize, name: Option<&'a str>) {
+        if let Behavior::Named(label, behavior) = self {
+            behavior.write_xml(writer, depth, name.or(Some(label)));
+            return;
+        }
+
+        let indent = depth * 2;
+        let xml = &mut writer.xml;
+        let _ = write!(xml, "{:indent$}", "");
+        let name_attribute = match name {
+            Some(name) => format!(" name=\"{}\"", Escape(name)),
+            None => String::new(),
+        };
+        match self {
+            Behavior::Action(action) => {
+                let tag = action.xml_tag();
+                let attributes = action.xml_attributes();
+                if !is_xml_name(tag) {
+                    writer.errors.push(XmlExportError::InvalidName(tag.into()));
+                } else if RESERVED_TAGS.contains(&tag) {
+                    writer.errors.push(XmlExportError::ReservedTag(tag.into()));
+                }
+                if let Some(label) = name
+                    && attributes.iter().any(|(name, _)| *name == "name")
+                {
+                    writer.errors.push(XmlExportError::DuplicateName {
+                        tag: tag.into(),
+                        label: label.into(),
+                    });
+                }
+
+                let ports = writer.models.entry(tag).or_default();
+                let _ = write!(xml, "<{tag}{name_attribute}");
+                for (name, value) in attributes {
+                    if !is_xml_name(name) {
+                        writer.errors.push(XmlExportError::InvalidName(name.into()));
+                    }
+                    ports.insert(name);
+                    let _ = write!(xml, " {name}=\"{}\"", Escape(&value));
+                }
+                xml.push_str("/>\n");
+            }
+            Behavior::Wait(target) => {
+                let msec = *target as u64;
+                if !(target.is_finite() && is_valid_wait(*target) && msec as f64 == *target) {
+                    writer
+                        .errors
+                        .push(XmlExportError::InvalidWait { target: *target });
+                }
+                let _ = writeln!(xml, "<Sleep{name_attribute} msec=\"{msec}\"/>");
+            }
+            Behavior::Invert(child) => {
+                let _ = writeln!(xml, "<Inverter{name_attribute}>");
+                child.write_xml(writer, depth + 1, None);
+                let _ = writeln!(writer.xml, "{:indent$}</Inverter>", "");
+            }
+            Behavior::Sequence(children) | Behavior::Select(children) => {
+                let tag = match self {
+                    Behavior::Sequence(_) => "Sequence",
+                    _ => "Fallback",
+                };
+                let _ = writeln!(xml, "<{tag}{name_attribute}>");
+                for child in children {
+                    child.write_xml(writer, depth + 1, None);
+                }
+                let _ = writeln!(writer.xml, "{:indent$}</{tag}>", "");
+            }
+            Behavior::Named(..) => unreachable!("written by its inner behavior"),
+        }
+    }
+}
+
+/// Tags that `Behavior::from_btcpp_xml` doesn't read back as actions
+const RESERVED_TAGS: [&str; 7] = [
+    "Sequence",
+    "Fallback",
+    "Inverter",
+    "Sleep",
+    "SubTree",
+    "Action",
+    "Condition",
+];
+
+/// Tags and attribute names are written as is, they can't be escaped
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Escapes the XML special characters of an attribute value
+struct Escape<'a>(&'a str);
+
+impl core::fmt::Display for Escape<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&apos;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{borrow::ToOwned, boxed::Box, vec};
+
+    use super::*;
+
+    #[derive(PartialEq)]
+    struct TestAction {
+        tag: String,
+        attributes: Vec<(String, String)>,
+    }
+
+    impl TestAction {
+        fn new(tag: &str, attributes: &[(&str, &str)]) -> Self {
+            Self {
+                tag: tag.to_owned(),
+                attributes: attributes
+                    .iter()
+                    .map(|(name, value)| ((*name).to_owned(), (*value).to_owned()))
+                    .collect(),
+            }
+        }
+    }
+
+    impl XmlAction for TestAction {
+        fn xml_tag(&self) -> &str {
+            &self.tag
+        }
+
+        fn xml_attributes(&self) -> Vec<(&str, String)> {
+            self.attributes
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.clone()))
+                .collect()
+        }
+    }
+
+    fn load(xml: &str) -> Behavior<TestAction> {
+        Behavior::from_btcpp_xml(xml, |leaf| {
+            let attributes: Vec<_> = leaf.attributes().collect();
+            Some(TestAction::new(leaf.tag(), &attributes))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_to_btcpp_xml() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Action(TestAction::new("Say", &[("message", "a < b & \"c\"")])),
+            Behavior::Select(vec![
+                Behavior::Invert(Box::new(Behavior::Action(TestAction::new(
+                    "IsDoorOpen",
+                    &[],
+                )))),
+                Behavior::Wait(1.0),
+            ]),
+            Behavior::Action(TestAction::new("Say", &[("voice", "low")])),
+        ]);
+
+        let xml = behavior.to_btcpp_xml().unwrap();
+        assert_eq!(
+            xml,
+            r#"<root BTCPP_format="4" main_tree_to_execute="Main">
+  <BehaviorTree ID="Main">
+    <Sequence>
+      <Say message="a &lt; b &amp; &quot;c&quot;"/>
+      <Fallback>
+        <Inverter>
+          <IsDoorOpen/>
+        </Inverter>
+        <Sleep msec="1"/>
+      </Fallback>
+      <Say voice="low"/>
+    </Sequence>
+  </BehaviorTree>
+  <TreeNodesModel>
+    <Action ID="IsDoorOpen"/>
+    <Action ID="Say">
+      <input_port name="message"/>
+      <input_port name="voice"/>
+    </Action>
+  </TreeNodesModel>
+</root>
+"#
+        );
+        assert!(load(&xml) == behavior);
+    }
+
+    #[test]
+    fn test_btcpp_xml_round_trip() {
+        let xml = r#"
+            <root BTCPP_format="4">
+                <BehaviorTree ID="Patrol">
+                    <Sequence>
+                        <Action ID="MoveBase" goal="kitchen"/>
+                        <Fallback>
+                            <Inverter><IsBatteryLow/></Inverter>
+                            <Sleep msec="1000"/>
+                        </Fallback>
+                    </Sequence>
+                </BehaviorTree>
+            </root>
+        "#;
+
+        let behavior = load(xml);
+        let exported = behavior.to_btcpp_xml().unwrap();
+        assert!(load(&exported) == behavior);
+        assert_eq!(load(&exported).to_btcpp_xml().unwrap(), exported);
+    }
+
+    #[test]
+    fn test_btcpp_xml_named() {
+        let behavior = Behavior::Named(
+            "Patrol".to_owned(),
+            Box::new(Behavior::Sequence(vec![
+                Behavior::Named("Nap".to_owned(), Box::new(Behavior::Wait(1.0))),
+                Behavior::Action(TestAction::new("Say", &[("name", "greet")])),
+            ])),
+        );
+
+        let xml = behavior.to_btcpp_xml().unwrap();
+        assert!(xml.contains(r#"<Sequence name="Patrol">"#));
+        assert!(xml.contains(r#"<Sleep name="Nap" msec="1"/>"#));
+        assert!(xml.contains(r#"<Say name="greet"/>"#));
+        assert!(load(&xml) == behavior);
+    }
+
+    #[test]
+    fn test_btcpp_xml_fractional_wait() {
+        let errors = Behavior::<TestAction>::Wait(0.4)
+            .to_btcpp_xml()
+            .err()
+            .unwrap();
+        assert_eq!(errors, [XmlExportError::InvalidWait { target: 0.4 }]);
+
+        let xml = Behavior::<TestAction>::Wait(2.0).to_btcpp_xml().unwrap();
+        assert!(xml.contains(r#"<Sleep msec="2"/>"#));
+    }
+
+    #[test]
+    fn test_btcpp_xml_reserved_tags() {
+        for tag in RESERVED_TAGS {
+            let behavior = Behavior::Action(TestAction::new(tag, &[]));
+            let errors = behavior.to_btcpp_xml().err().unwrap();
+            assert_eq!(errors, [XmlExportError::ReservedTag(tag.to_owned())]);
+        }
+
+        let behavior = Behavior::Action(TestAction::new("SequenceStep", &[]));
+        let xml = behavior.to_btcpp_xml().unwrap();
+        assert!(load(&xml) == behavior);
+    }
+
+    #[test]
+    fn test_to_btcpp_xml_errors() {
+        let behavior = Behavior::Sequence(vec![
+            Behavior::Wait(-1.0),
+            Behavior::Wait(f64::NAN),
+            Behavior::Action(TestAction::new("Say Hi", &[("a\"b", "c")])),
+            Behavior::Named(
+                "Greet".to_owned(),
+                Box::new(Behavior::Action(TestAction::new("Say", &[("name", "hi")]))),
+            ),
+        ]);
+
+        let errors = behavior.to_btcpp_xml().err().unwrap();
+        assert_eq!(errors.len(), 5);
+        assert_eq!(errors[0], XmlExportError::InvalidWait { target: -1.0 });
+        assert!(matches!(errors[1], XmlExportError::InvalidWait { target } if target.is_nan()));
+        assert_eq!(errors[2], XmlExportError::InvalidName("Say Hi".to_owned()));
+        assert_eq!(errors[3], XmlExportError::InvalidName("a\"b".to_owned()));
+        assert_eq!(
+            errors[4],
+            XmlExportError::DuplicateName {
+                tag: "Say".to_owned(),
+                label: "Greet".to_owned()
+            }
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Checks that `behaviortree` and `async_behaviortree` run the same trees the same way.
+//!
+//! A [`Node`] describes a tree with scripted leaves, it is built for both executors
+//! and ticked by their harnesses. Both must report the same status for every node after every tick.
+//!
+//! Trees that don't loop are ticked until their root completes: the sync tree then keeps
+//! its statuses while the future of the async tree resets its nodes and returns.
+
+use std::collections::BTreeMap;
+
+/// Time between two ticks, waits are whole multiples of it
+pub const DT: f64 = 1.0;
+
+/// Tree built the same way with both executors
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    /// Runs for `running` ticks, then succeeds or fails
+    Action {
+        running: usize,
+        success: bool,
+    },
+    /// Waits for `ticks` ticks
+    Wait(usize),
+    Invert(Box<Node>),
+    Sequence(Vec<Node>),
+    Select(Vec<Node>),
+}
+
+impl Node {
+    pub fn to_sync(&self) -> behaviortree::Behavior<behaviortree::MockAction> {
+        use behaviortree::{Behavior, MockAction};
+
+        match self {
+            Node::Action { running, success } => {
+                let action = MockAction::new("Action").running(*running);
+                Behavior::Action(if *success {
+                    action.success()
+                } else {
+                    action.failure()
+                })
+            }
+            Node::Wait(ticks) => Behavior::Wait(*ticks as f64 * DT),
+            Node::Invert(child) => Behavior::Invert(Box::new(child.to_sync())),
+            Node::Sequence(children) => {
+                Behavior::Sequence(children.iter().map(Node::to_sync).collect())
+            }
+            Node::Select(children) => {
+                Behavior::Select(children.iter().map(Node::to_sync).collect())
+            }
+        }
+    }
+
+    pub fn to_async(&self) -> async_behaviortree::Behavior<async_behaviortree::MockAction> {
+        use async_behaviortree::{Behavior, MockAction};
+
+        match self {
+            Node::Action { running, success } => {
+                let action = MockAction::new("Action").running(*running);
+                Behavior::Action(if *success {
+                    action.success()
+                } else {
+                    action.failure()
+                })
+            }
+            Node::Wait(ticks) => Behavior::Wait(*ticks as f64 * DT),
+            Node::Invert(child) => Behavior::Invert(Box::new(child.to_async())),
+            Node::Sequence(children) => {
+                Behavior::Sequence(children.iter().map(Node::to_async).collect())
+            }
+            Node::Select(children) => {
+                Behavior::Select(children.iter().map(Node::to_async).collect())
+            }
+        }
+    }
+}
+
+/// Statuses of every node after every tick by path, written as `S`, `F`, `R` or `-` (idle)
+pub type Timelines = BTreeMap<String, String>;
+
+/// Ticks `node` at most `ticks` times with `behaviortree`
+pub fn run_sync(node: &Node, should_loop: bool, ticks: usize) -> Timelines {
+    use behaviortree::{Status, TreeHarness};
+
+    let mut harness = TreeHarness::new(node.to_sync(), should_loop, (), ());
+    for _ in 0..ticks {
+        if harness.tick(DT) != Status::Running && !should_loop {
+            break;
+        }
+    }
+    harness
+        .timelines()
+        .map(|(path, timeline)| {
+            let timeline = timeline.iter().map(|status| match status {
+                Some(Status::Success) => 'S',
+                Some(Status::Failure) => 'F',
+                Some(Status::Running) => 'R',
+                None => '-',
+            });
+            (path.to_owned(), timeline.collect())
+        })
+        .collect()
+}
+
+/// Ticks the executor of `node` at most `ticks` times with `async_behaviortree`
+pub fn run_async(node: &Node, should_loop: bool, ticks: usize) -> Timelines {
+    use async_behaviortree::{AsyncTreeHarness, MockRunner, Status};
+
+    let mut harness = AsyncTreeHarness::new(node.to_async(), should_loop, MockRunner);
+    for _ in 0..ticks {
+        if harness.tick(DT) != Some(Status::Running) && !should_loop {
+            break;
+        }
+    }
+    harness
+        .timelines()
+        .map(|(path, timeline)| {
+            let timeline = timeline.iter().map(|status| match status {
+                Some(Status::Success) => 'S',
+                Some(Status::Failure) => 'F',
+                Some(Status::Running) => 'R',
+                None => '-',
+            });
+            (path.to_owned(), timeline.collect())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const TICKS: usize = 12;
+
+    fn action(running: usize, success: bool) -> Node {
+        Node::Action { running, success }
+    }
+
+    #[track_caller]
+    fn assert_conforms(node: &Node, should_loop: bool) -> Timelines {
+        let timelines = run_sync(node, should_loop, TICKS);
+        assert_eq!(timelines, run_async(node, should_loop, TICKS));
+        timelines
+    }
+
+    #[test]
+    fn test_sequence_conforms() {
+        // The sync sequence returns `Running` after a child succeeds, the async one yields
+        let node = Node::Sequence(vec![action(0, true), action(1, true), action(0, false)]);
+        let timelines = assert_conforms(&node, false);
+        assert_eq!(timelines["root/Sequence"], "RRRF");
+        assert_eq!(timelines["root/Sequence[1]/Action"], "-RSS");
+        assert_conforms(&node, true);
+    }
+
+    #[test]
+    fn test_select_conforms() {
+        let node = Node::Select(vec![action(1, false), Node::Wait(2), action(0, true)]);
+        let timelines = assert_conforms(&node, false);
+        assert_eq!(timelines["root/Select"], "RRRS");
+        assert_conforms(&node, true);
+    }
+
+    #[test]
+    fn test_invert_conforms() {
+        let node = Node::Invert(Box::new(Node::Sequence(vec![
+            Node::Wait(1),
+            action(2, false),
+        ])));
+        assert_conforms(&node, false);
+        assert_conforms(&node, true);
+    }
+
+    fn node() -> impl Strategy<Value = Node> {
+        let leaf = prop_oneof![
+            (0..3usize, any::<bool>()).prop_map(|(running, success)| action(running, success)),
+            (1..3usize).prop_map(Node::Wait),
+        ];
+        leaf.prop_recursive(4, 24, 4, |inner| {
+            prop_oneof![
+                inner
+                    .clone()
+                    .prop_map(|child| Node::Invert(Box::new(child))),
+                prop::collection::vec(inner.clone(), 1..4).prop_map(Node::Sequence),
+                prop::collection::vec(inner, 1..4).prop_map(Node::Select),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_random_trees_conform(node in node(), should_loop in any::<bool>()) {
+            prop_assert_eq!(
+                run_sync(&node, should_loop, TICKS),
+                run_async(&node, should_loop, TICKS)
+            );
+        }
+    }
+}